@@ -1,8 +1,9 @@
 // extern crate proc_macro;
 use proc_macro::TokenStream;
-use proc_macro2::TokenTree;
-use quote::quote;
-use syn::{parse_macro_input, AngleBracketedGenericArguments, DeriveInput, PathSegment};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, DeriveInput, PathSegment};
 
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
@@ -38,32 +39,604 @@ pub fn derive(input: TokenStream) -> TokenStream {
     //!     assert_eq!(command.executable, "cargo");
     //! }
     //! ```
+    //! 
+    //! ## Default Values
+    //! 
+    //! Fields with `#[builder(default)]` are filled in with `Default::default()` when
+    //! they are not set, and fields with `#[builder(default = "...")]` with the given
+    //! expression instead. The expression can use the fields declared before it by name.
+    //! 
+    //! `#[builder(default)]` on the struct uses the struct's own `Default` impl as the
+    //! base for every unset field without a default of its own. Fields with `each` are
+    //! not part of this and always start out empty. The fields are taken out of the base
+    //! with `mem::take`, so the struct can implement `Drop` but the types of the fields
+    //! taken from it have to be `Default` as well.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! pub struct Server {
+    //!     host: String,
+    //!     #[builder(default = "80")]
+    //!     port: u16,
+    //!     #[builder(default = "format!(\"{}:{}\", host, port)")]
+    //!     addr: String,
+    //!     #[builder(default)]
+    //!     verbose: bool,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let server = Server::builder().host("localhost".to_owned()).build().unwrap();
+    //!     assert_eq!(server.addr, "localhost:80");
+    //!     assert!(!server.verbose);
+    //! }
+    //! ```
+    //! 
+    //! ## Collections
+    //! 
+    //! `#[builder(each = "...")]` works for any collection that is `Default` and `Extend`s
+    //! its items. The setter it names adds one item, or takes a key and a value for a map
+    //! like `HashMap<K, V>` or `BTreeMap<K, V>`, and an `extend_<field>` method adds all the
+    //! items of an iterator at once.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! use std::collections::{BTreeSet, HashMap};
+    //! 
+    //! #[derive(Builder)]
+    //! pub struct Request {
+    //!     #[builder(each = "header")]
+    //!     headers: HashMap<String, String>,
+    //!     #[builder(each = "flag")]
+    //!     flags: BTreeSet<String>,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let request = Request::builder()
+    //!         .header("Accept".to_owned(), "*/*".to_owned())
+    //!         .flag("gzip".to_owned())
+    //!         .extend_flags(vec!["tls".to_owned()])
+    //!         .build()
+    //!         .unwrap();
+    //!     assert_eq!(request.headers["Accept"], "*/*");
+    //!     assert_eq!(request.flags.len(), 2);
+    //! }
+    //! ```
+    //! 
+    //! ## Build Errors
+    //! 
+    //! `build()` returns a `{Name}BuilderError` that says which field was not set,
+    //! carries the message of a failed validation, the error of a sub-builder, or of an
+    //! environment variable read by `from_env()`:
+    //! 
+    //! ```ignore
+    //! pub enum CommandBuilderError {
+    //!     UninitializedField(&'static str),
+    //!     Validation(String),
+    //!     SubBuilder(&'static str, String),
+    //!     Env(&'static str, String),
+    //! }
+    //! ```
+    //! 
+    //! It implements `Display` and `Error` and converts `From<String>` into `Validation`.
+    //! `#[builder(error = "MyError")]` on the struct makes `build()` return `MyError`
+    //! instead, which needs to implement `From<{Name}BuilderError>`.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! pub struct Command {
+    //!     executable: String,
+    //! }
+    //! 
+    //! fn main() {
+    //!     match Command::builder().build() {
+    //!         Err(CommandBuilderError::UninitializedField(field)) => assert_eq!(field, "executable"),
+    //!         _ => unreachable!(),
+    //!     }
+    //! }
+    //! ```
+    //! 
+    //! ## Validation
+    //! 
+    //! `#[builder(validate = "path::to::fn")]` on the struct calls
+    //! `fn(&{Name}Builder) -> Result<(), String>` at the start of `build()`, and on a field
+    //! calls `fn(&T) -> Result<(), String>` with the final value of the field once it is
+    //! known. An `Err` makes `build()` return it as a `Validation` error.
+    //! 
+    //! A typestate builder with a validation returns a `Result` from `build()` as well.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! #[builder(validate = "Self::check_range")]
+    //! pub struct Range {
+    //!     min: u32,
+    //!     #[builder(validate = "not_zero")]
+    //!     max: u32,
+    //! }
+    //! 
+    //! impl RangeBuilder {
+    //!     fn check_range(&self) -> Result<(), String> {
+    //!         match (self.min, self.max) {
+    //!             (Some(min), Some(max)) if min > max => Err(format!("{} > {}", min, max)),
+    //!             _ => Ok(()),
+    //!         }
+    //!     }
+    //! }
+    //! 
+    //! fn not_zero(max: &u32) -> Result<(), String> {
+    //!     if *max == 0 { Err("max is 0".to_owned()) } else { Ok(()) }
+    //! }
+    //! 
+    //! fn main() {
+    //!     assert!(Range::builder().min(2).max(1).build().is_err());
+    //!     assert!(Range::builder().min(0).max(0).build().is_err());
+    //! }
+    //! ```
+    //! 
+    //! ## Builder Patterns
+    //! 
+    //! By default the setters take and return `&mut self` and `build(&self)` clones the
+    //! fields out of the builder. `#[builder(pattern = "owned")]` makes the setters take
+    //! and return `self` and `build(self)` move the fields instead, so the field types
+    //! don't need to be `Clone`. `#[builder(pattern = "immutable")]` makes the setters take
+    //! `&self` and return a new builder with the change.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! use std::sync::mpsc::{channel, Receiver};
+    //! 
+    //! #[derive(Builder)]
+    //! #[builder(pattern = "owned")]
+    //! pub struct Worker {
+    //!     jobs: Receiver<String>,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let (_, jobs) = channel();
+    //!     let worker = Worker::builder().jobs(jobs).build().unwrap();
+    //! }
+    //! ```
+    //! 
+    //! ## Setter Conversions
+    //! 
+    //! `#[builder(setter(into))]` on a field makes its setters generic over anything that
+    //! is `Into` the type they take, so `.executable("cargo")` works for a `String`. On
+    //! the struct it does the same for every field, which `setter(into = false)` on a
+    //! field turns off again.
+    //! 
+    //! The setter of an `Option<T>` field takes a `T` by default. With
+    //! `#[builder(setter(strip_option = false))]` on the field (or on the struct for all
+    //! of them) it takes the `Option<T>` itself, and a `None` given to it counts as set so
+    //! it is kept over a `default` or in `merge()`.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! #[builder(setter(into))]
+    //! pub struct Command {
+    //!     executable: String,
+    //!     #[builder(each = "arg")]
+    //!     args: Vec<String>,
+    //!     #[builder(setter(strip_option = false))]
+    //!     current_dir: Option<String>,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let command = Command::builder()
+    //!         .executable("cargo")
+    //!         .arg("build")
+    //!         .current_dir(None)
+    //!         .build()
+    //!         .unwrap();
+    //!     assert_eq!(command.executable, "cargo");
+    //! }
+    //! ```
+    //! 
+    //! ## Custom and Fallible Setters
+    //! 
+    //! `#[builder(setter(custom))]` keeps a field in the builder and in `build()` but
+    //! leaves its setter out, so it can be written by hand in an `impl` of the builder.
+    //! `#[builder(try_setter)]` adds a `try_<field>()` that converts its argument with
+    //! `TryInto` and returns the error of the conversion if it fails. It can be put on
+    //! the struct for every field. A typestate builder only allows `setter(custom)` on the
+    //! fields it doesn't require.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! pub struct Server {
+    //!     #[builder(setter(custom))]
+    //!     host: String,
+    //!     #[builder(try_setter)]
+    //!     port: u16,
+    //! }
+    //! 
+    //! impl ServerBuilder {
+    //!     fn host(&mut self, host: &str) -> &mut Self {
+    //!         self.host = Some(host.to_lowercase());
+    //!         self
+    //!     }
+    //! }
+    //! 
+    //! fn main() {
+    //!     let mut builder = Server::builder();
+    //!     builder.host("LOCALHOST");
+    //!     assert!(builder.try_port(70_000).is_err());
+    //!     let server = builder.try_port(80).unwrap().build().unwrap();
+    //!     assert_eq!(server.host, "localhost");
+    //! }
+    //! ```
+    //! 
+    //! ## Enums
+    //! 
+    //! Deriving the builder for an enum generates one builder per variant, named
+    //! `{Enum}{Variant}Builder` and returned from `Enum::{variant}_builder()`. The fields
+    //! of the variants work the same way as those of a struct and the options on the
+    //! enum apply to all of its builders, except for `#[builder(default)]` which needs a
+    //! struct.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! pub enum Msg {
+    //!     Connect { host: String, port: u16 },
+    //!     Ping,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let msg = Msg::connect_builder()
+    //!         .host("localhost".to_owned())
+    //!         .port(80)
+    //!         .build()
+    //!         .unwrap();
+    //!     assert!(matches!(msg, Msg::Connect { port: 80, .. }));
+    //!     assert!(matches!(Msg::ping_builder().build().unwrap(), Msg::Ping));
+    //! }
+    //! ```
+    //! 
+    //! ## Skipped Fields
+    //! 
+    //! `#[builder(skip)]` leaves a field out of the builder and `#[builder(setter(skip))]`
+    //! keeps it in the builder without a setter. Either way `build()` fills it from its
+    //! `#[builder(default = "...")]`, the default of the struct, or `Default` when it has
+    //! neither. A skipped field can be the only one to use a generic param, like a
+    //! `PhantomData<T>` marker.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! use std::sync::{Arc, Mutex};
+    //! 
+    //! #[derive(Builder)]
+    //! pub struct Client {
+    //!     url: String,
+    //!     #[builder(skip)]
+    //!     cache: Arc<Mutex<Vec<String>>>,
+    //!     #[builder(setter(skip), default = "3")]
+    //!     retries: u32,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let client = Client::builder().url("localhost".to_owned()).build().unwrap();
+    //!     assert!(client.cache.lock().unwrap().is_empty());
+    //!     assert_eq!(client.retries, 3);
+    //! }
+    //! ```
+    //! 
+    //! ## Existing Values and Getters
+    //! 
+    //! The builder of a struct is `From` the struct, with every field set to the value in
+    //! it, to change a few fields of an existing value and build it again. The values in a
+    //! builder can be read back with its `get_<field>()` methods, which return an `Option`
+    //! of a reference for the fields that might not be set.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! pub struct Command {
+    //!     executable: String,
+    //!     #[builder(each = "arg")]
+    //!     args: Vec<String>,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let base = Command { executable: "cargo".to_owned(), args: vec![] };
+    //!     let mut builder = CommandBuilder::from(base);
+    //!     builder.arg("test".to_owned());
+    //!     assert_eq!(builder.get_executable(), Some(&"cargo".to_owned()));
+    //!     assert_eq!(builder.build().unwrap().args, vec!["test"]);
+    //! }
+    //! ```
+    //! 
+    //! ## Merging Builders
+    //! 
+    //! `merge()` combines another builder into this one, so the layers of a config can
+    //! each be loaded into a builder and merged in order. A field that is set in the
+    //! other builder replaces the value in this one and the items of an `each`
+    //! collection are appended, which `#[builder(merge = "replace" | "append" | "keep")]`
+    //! changes per field. Sub-builders are merged with their own `merge()`. Typestate
+    //! builders have no `merge()`.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! pub struct Command {
+    //!     executable: String,
+    //!     #[builder(each = "arg")]
+    //!     args: Vec<String>,
+    //!     #[builder(merge = "keep")]
+    //!     current_dir: Option<String>,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let mut defaults = Command::builder();
+    //!     defaults.executable("cargo".to_owned()).arg("build".to_owned()).current_dir("/".to_owned());
+    //!     let mut cli = Command::builder();
+    //!     cli.arg("--release".to_owned()).current_dir("/tmp".to_owned());
+    //! 
+    //!     let command = defaults.merge(cli).build().unwrap();
+    //!     assert_eq!(command.args, vec!["build", "--release"]);
+    //!     assert_eq!(command.current_dir, Some("/".to_owned()));
+    //! }
+    //! ```
+    //! 
+    //! ## Environment Variables
+    //! 
+    //! `#[builder(env_prefix = "APP_")]` on the struct generates a `from_env()` that starts
+    //! a builder with every field set from the variable named by the prefix and the field
+    //! in upper case, like `APP_PORT`. `#[builder(env = "...")]` names the variable of a
+    //! single field instead, and works without a prefix too, while `#[builder(env = false)]`
+    //! leaves a field out, like one whose type isn't `FromStr`. The values are parsed with
+    //! `FromStr` and the items of an `each` collection are split on `,`, or the
+    //! `#[builder(env_separator = "...")]` of the field, with the entries of a map given as
+    //! `key=value`. A variable that can't be parsed is returned as an `Env` error with its
+    //! name. Fields without a setter, from `setter(skip)` or `setter(custom)`, and
+    //! sub-builders are not read.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! #[builder(env_prefix = "DOC_SERVER_")]
+    //! pub struct Server {
+    //!     host: String,
+    //!     port: u16,
+    //! }
+    //! 
+    //! fn main() {
+    //!     std::env::set_var("DOC_SERVER_PORT", "80");
+    //!     let mut builder = ServerBuilder::from_env().unwrap();
+    //!     builder.host("localhost".to_owned());
+    //!     assert_eq!(builder.build().unwrap().port, 80);
+    //! }
+    //! ```
+    //! 
+    //! ## Sub-Builders
+    //! 
+    //! A field whose type derives `Builder` too can be built in place with
+    //! `#[builder(sub_builder)]`. The builder then stores the `{Type}Builder` of the field
+    //! and hands it out from a `&mut self` method in place of the setter, and `build()`
+    //! builds it with the rest of the fields. An error from it is reported under the
+    //! name of the field, like `server.port is not set.`. The builder type can be given
+    //! with `#[builder(sub_builder = "...")]` when it is not `{Type}Builder`, or with
+    //! `#[builder(sub_builder(name = "...", build_fn = "..."))]` together with the name
+    //! of its `build()`.
+    //! 
+    //! The builder of the field is built and merged through a reference, so it has to
+    //! use the default mutable pattern. When its type is in another module it needs
+    //! `#[builder(vis = "pub", setter_vis = "pub")]`, or a visibility that reaches this
+    //! builder, for its `Type::builder()` and `merge()`.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! pub struct Server {
+    //!     host: String,
+    //!     port: u16,
+    //! }
+    //! 
+    //! #[derive(Builder)]
+    //! pub struct Config {
+    //!     #[builder(sub_builder)]
+    //!     server: Server,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let mut builder = Config::builder();
+    //!     builder.server().host("localhost".to_owned());
+    //!     let err = builder.build().err().unwrap();
+    //!     assert_eq!(err.to_string(), "server.port is not set.");
+    //! 
+    //!     builder.server().port(80);
+    //!     assert_eq!(builder.build().unwrap().server.port, 80);
+    //! }
+    //! ```
+    //! 
+    //! ## Naming and Visibility
+    //! 
+    //! The builder is a `pub struct {Name}Builder` with private setters and a private
+    //! `Name::builder()`. The options on the struct change that to fit the API of a crate:
+    //! 
+    //! - `#[builder(name = "...")]` names the builder of a struct.
+    //! - `#[builder(vis = "...")]` is the visibility of the builder, its error and
+    //!   `Name::builder()`.
+    //! - `#[builder(setter_vis = "...")]` is the visibility of the setters.
+    //! - `#[builder(derive(...))]` derives the traits on the builder.
+    //! - `#[builder(build_fn(name = "..."))]` renames `build()`.
+    //! 
+    //! ```
+    //! mod config {
+    //!     use derive_builder::Builder;
+    //! 
+    //!     #[derive(Builder)]
+    //!     #[builder(name = "ConfigOptions", vis = "pub", setter_vis = "pub")]
+    //!     #[builder(derive(Debug, Clone), build_fn(name = "finish"))]
+    //!     pub struct Config {
+    //!         pub name: String,
+    //!     }
+    //! }
+    //! 
+    //! fn main() {
+    //!     let mut options: config::ConfigOptions = config::Config::builder();
+    //!     options.name("app".to_owned());
+    //!     assert_eq!(options.clone().finish().unwrap().name, "app");
+    //! }
+    //! ```
+    //! 
+    //! ## Serde
+    //! 
+    //! With the `serde` feature `#[builder(derive(Deserialize))]` makes the builder
+    //! deserializable, with every field missing from the input left unset. A partial
+    //! config can then be loaded into the builder, changed with the setters and checked
+    //! for the fields that are still missing by `build()`. The types of sub-builder
+    //! fields have to derive `Deserialize` on their builders as well. The fields without a
+    //! setter, from `setter(skip)` or `setter(custom)`, are not read from the input.
+    //! 
+    //! ```ignore
+    //! use derive_builder::Builder;
+    //! use serde::Deserialize;
+    //! 
+    //! #[derive(Builder)]
+    //! #[builder(derive(Deserialize))]
+    //! pub struct Server {
+    //!     host: String,
+    //!     port: u16,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let mut builder: ServerBuilder = serde_json::from_str(r#"{ "host": "localhost" }"#).unwrap();
+    //!     assert_eq!(builder.build().unwrap_err().to_string(), "port is not set.");
+    //!     builder.port(80);
+    //!     assert_eq!(builder.build().unwrap().port, 80);
+    //! }
+    //! ```
+    //! 
+    //! ## Const Builders
+    //! 
+    //! `#[builder(const)]` makes `Name::builder()`, the setters and `build()` into
+    //! `const fn`s, so a builder can make the value of a `const` or `static`. The setters
+    //! take and return the builder by value and `build()` returns the struct directly,
+    //! panicking with the name of a field that is not set. The fields need types that
+    //! can be dropped in a `const fn`, like the `Copy` ones, and the options that would
+    //! call anything that is not `const` can't be used, like `each`, `validate`,
+    //! `setter(into)` or a `default` without an expression. A const builder has no
+    //! `merge()`.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! #[builder(const)]
+    //! pub struct Server {
+    //!     host: &'static str,
+    //!     port: u16,
+    //! }
+    //! 
+    //! static SERVER: Server = Server::builder().host("localhost").port(80).build();
+    //! 
+    //! fn main() {
+    //!     assert_eq!(SERVER.port, 80);
+    //! }
+    //! ```
+    //! 
+    //! ## `no_std`
+    //! 
+    //! `#[builder(no_std)]` makes the generated code use paths into `core` and `alloc`
+    //! instead of `std`, for crates without the standard library. The crate needs an
+    //! `extern crate alloc` for the `String` in the build error. A `no_std` builder has
+    //! no `from_env()`.
+    //! 
+    //! ## Generics
+    //! 
+    //! Lifetimes, type and const params and the where-clause of the struct are all
+    //! carried over to the builder. Since `build()` clones the fields out of the builder
+    //! it is only available when the field types are `Clone`, unless the builder uses
+    //! the owned pattern.
+    //! 
+    //! ## Typestate Builders
+    //! 
+    //! Adding `#[builder(typestate)]` to the struct generates a builder that tracks which
+    //! of the required fields have been set in its type. Every field that is not an
+    //! `Option` and has no `each`, `default`, `skip` or `setter(skip)`, on a struct without
+    //! `#[builder(default)]`, gets its own type parameter that flips from
+    //! `{Name}BuilderUnset` to `{Name}BuilderSet` when its setter is called, and `build()`
+    //! only exists once all of them are set - so a missing field is a compile error
+    //! instead of a runtime one, and `build()` returns the struct directly.
+    //! 
+    //! The setters of a typestate builder take and return the builder by value.
+    //! 
+    //! ```
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! #[builder(typestate)]
+    //! pub struct Command {
+    //!     executable: String,
+    //!     #[builder(each = "arg")]
+    //!     args: Vec<String>,
+    //!     current_dir: Option<String>,
+    //! }
+    //! 
+    //! fn main() {
+    //!     let command = Command::builder()
+    //!         .arg("build".to_owned())
+    //!         .executable("cargo".to_owned())
+    //!         .build();
+    //!     assert_eq!(command.executable, "cargo");
+    //! }
+    //! ```
+    //! 
+    //! ```compile_fail
+    //! use derive_builder::Builder;
+    //! 
+    //! #[derive(Builder)]
+    //! #[builder(typestate)]
+    //! pub struct Command {
+    //!     executable: String,
+    //! }
+    //! 
+    //! fn main() {
+    //!     // `executable` was never set so there is no `build()`
+    //!     let command = Command::builder().build();
+    //! }
+    //! ```
    
+
     // -=-=- Helper Functions -=-=- //
 
-    // Function to get the inner type of a `syn::Type` and panic! if it doesn't find one.
-    // Note: you should check your type before traversing
-    let get_inner_type = |ty: &syn::Type, sub_ty: usize| {
+    // Function to get the inner types of a `syn::Type`, like the `T` of a `Vec<T>` or the
+    // `K` and `V` of a `HashMap<K, V>`. It is empty for a type without any.
+    let get_inner_types = |ty: &syn::Type| {
         if let syn::Type::Path(ref p) = ty {
-            let PathSegment {arguments, ..} = p.path.segments.last().unwrap();
-            if let syn::GenericArgument::Type(ty) =
-            if let syn::PathArguments::AngleBracketed(AngleBracketedGenericArguments {args, ..}) = arguments {
-                &args[sub_ty]
-            } else { panic!() } {
-                ty.to_owned()
-            } else { panic!() }
-        } else { panic!() }
+            if let Some(PathSegment {arguments: syn::PathArguments::AngleBracketed(ref args), ..}) = p.path.segments.last() {
+                return args.args.iter().filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => Some(ty.to_owned()),
+                    _ => None,
+                }).collect();
+            }
+        }
+        Vec::new()
     };
 
-    // Check if a syn:Type has the ident == to string `s`
-    let ty_is_type = |ty: &syn::Type, s: &str| {
+    // Check if a syn::Type is a map like `HashMap<K, V>` or `BTreeMap<K, V>`
+    let ty_is_map = |ty: &syn::Type| {
         if let syn::Type::Path(ref p) = ty {
-            p.path.segments.last().unwrap().ident == s
+            p.path.segments.last().is_some_and(|s| s.ident.to_string().ends_with("Map"))
         } else {
             false
         }
     };
-    // Check if a syn::Type is an Option<...> and return the inner type or None 
+    // Check if a syn::Type is an Option<...> and return the inner type or None
     let ty_is_option = |ty: &syn::Type| {
         if let syn::Type::Path(ref p) = ty {
             if (
@@ -79,215 +652,1526 @@ pub fn derive(input: TokenStream) -> TokenStream {
                 p.path.segments[1].ident == "option" &&
                 p.path.segments[2].ident == "Option"
             ) {
-                Some(get_inner_type(ty, 0))
+                get_inner_types(ty).into_iter().next()
             } else { None }
         } else { None }
     };
 
-    // Make a standardized attr error and pass the tokens for the span
-    let make_attr_error = |t: &dyn quote::ToTokens| {
-        syn::Error::new_spanned(t, "expected `builder(each = \"...\")`").to_compile_error()
+    // the `{Type}Builder` of a field's type for `builder(sub_builder)`, keeping the path
+    // and generics of the type. It is `None` for a type that is not a path.
+    let sub_builder_ty = |ty: &syn::Type| {
+        let syn::Type::Path(p) = ty else { return None; };
+        let mut p = p.to_owned();
+        let last = p.path.segments.last_mut()?;
+        last.ident = format_ident!("{}Builder", last.ident);
+        Some(syn::Type::Path(p))
+    };
+
+    // Make a standardized attr error and pass the tokens for the span
+    let make_attr_error = |t: &dyn quote::ToTokens| {
+        syn::Error::new_spanned(t, "expected `builder(each = \"...\")`")
+    };
+
+    // parse the args of a `builder(setter(...))` attr into `setter`
+    // each of `into`, `strip_option`, `skip` and `custom` can be turned off again with `= false`
+    let parse_setter_attrs = |meta: &syn::meta::ParseNestedMeta, setter: &mut SetterAttrs| {
+        meta.parse_nested_meta(|meta| {
+            let value = if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<syn::LitBool>()?.value
+            } else { true };
+            if meta.path.is_ident("into") {
+                setter.into = Some(value);
+            } else if meta.path.is_ident("strip_option") {
+                setter.strip_option = Some(value);
+            } else if meta.path.is_ident("skip") {
+                setter.skip = Some(value);
+            } else if meta.path.is_ident("custom") {
+                setter.custom = Some(value);
+            } else {
+                return Err(meta.error("expected `into`, `strip_option`, `skip` or `custom`"));
+            }
+            Ok(())
+        })
+    };
+
+    // parse the `#[builder(...)]` attrs on a field
+    // returns the options of the field or the error for a malformed builder attr
+    let parse_field_attrs = |field: &syn::Field| {
+        let mut field_attrs = FieldAttrs::default();
+        for attr in &field.attrs {
+            if !attr.path().is_ident("builder") { continue; }
+            let list = attr.meta.require_list()?;
+            if list.tokens.is_empty() { return Err(make_attr_error(list)); }
+            list.parse_nested_meta(|meta| {
+                if meta.path.is_ident("each") {
+                    // builder(each = "...")
+                    let each: syn::LitStr = meta.value()?.parse()?;
+                    field_attrs.each = Some(each.parse()?);
+                } else if meta.path.is_ident("default") {
+                    // builder(default) or builder(default = "...")
+                    field_attrs.default = Some(if meta.input.peek(syn::Token![=]) {
+                        let expr: syn::LitStr = meta.value()?.parse()?;
+                        FieldDefault::Expr(expr.parse()?)
+                    } else { FieldDefault::Trait });
+                } else if meta.path.is_ident("setter") {
+                    // builder(setter(...))
+                    parse_setter_attrs(&meta, &mut field_attrs.setter)?;
+                } else if meta.path.is_ident("validate") {
+                    // builder(validate = "...")
+                    let validate: syn::LitStr = meta.value()?.parse()?;
+                    field_attrs.validate = Some(validate.parse()?);
+                } else if meta.path.is_ident("env") {
+                    // builder(env = "...") or builder(env = false)
+                    let value = meta.value()?;
+                    field_attrs.env = Some(if value.peek(syn::LitBool) {
+                        let read: syn::LitBool = value.parse()?;
+                        if read.value {
+                            return Err(syn::Error::new_spanned(read, "expected the name of a variable or `false`"));
+                        }
+                        FieldEnv::Skip
+                    } else {
+                        FieldEnv::Var(value.parse()?)
+                    });
+                } else if meta.path.is_ident("env_separator") {
+                    // builder(env_separator = "...")
+                    field_attrs.env_separator = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("merge") {
+                    // builder(merge = "...")
+                    let merge: syn::LitStr = meta.value()?.parse()?;
+                    field_attrs.merge = Some(match merge.value().as_str() {
+                        "replace" => Merge::Replace,
+                        "append" => Merge::Append,
+                        "keep" => Merge::Keep,
+                        _ => return Err(syn::Error::new_spanned(
+                            merge, "expected `replace`, `append` or `keep`"
+                        )),
+                    });
+                } else if meta.path.is_ident("try_setter") {
+                    // builder(try_setter)
+                    field_attrs.try_setter = true;
+                } else if meta.path.is_ident("skip") {
+                    // builder(skip)
+                    field_attrs.skip = true;
+                } else if meta.path.is_ident("sub_builder") {
+                    // builder(sub_builder), builder(sub_builder = "...") or
+                    // builder(sub_builder(name = "...", build_fn = "..."))
+                    let (mut ty, mut build_fn) = (None, None);
+                    if meta.input.peek(syn::Token![=]) {
+                        let name: syn::LitStr = meta.value()?.parse()?;
+                        ty = Some(name.parse()?);
+                    } else if meta.input.peek(syn::token::Paren) {
+                        meta.parse_nested_meta(|meta| {
+                            if meta.path.is_ident("name") {
+                                let name: syn::LitStr = meta.value()?.parse()?;
+                                ty = Some(name.parse()?);
+                            } else if meta.path.is_ident("build_fn") {
+                                let name: syn::LitStr = meta.value()?.parse()?;
+                                build_fn = Some(name.parse()?);
+                            } else {
+                                return Err(meta.error("expected `name` or `build_fn`"));
+                            }
+                            Ok(())
+                        })?;
+                    }
+                    let ty = match ty {
+                        Some(ty) => ty,
+                        None => sub_builder_ty(&field.ty).ok_or_else(|| syn::Error::new_spanned(
+                            &field.ty, "expected a type that derives `Builder`, or `builder(sub_builder = \"...\")`"
+                        ))?,
+                    };
+                    field_attrs.sub_builder = Some(SubBuilder {
+                        ty,
+                        build_fn: build_fn.unwrap_or_else(|| format_ident!("build")),
+                    });
+                } else {
+                    return Err(make_attr_error(list));
+                }
+                Ok(())
+            })?;
+        }
+        syn::Result::Ok(field_attrs)
+    };
+
+    // parse the `#[builder(...)]` attrs on the struct
+    // returns the options of the struct or the error for a malformed builder attr
+    let parse_struct_attrs = |attrs: &Vec<syn::Attribute>| {
+        let mut struct_attrs = StructAttrs::default();
+        for attr in attrs {
+            if !attr.path().is_ident("builder") { continue; }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("typestate") {
+                    struct_attrs.typestate = true;
+                } else if meta.path.is_ident("default") {
+                    struct_attrs.default = true;
+                } else if meta.path.is_ident("validate") {
+                    let validate: syn::LitStr = meta.value()?.parse()?;
+                    struct_attrs.validate = Some(validate.parse()?);
+                } else if meta.path.is_ident("setter") {
+                    parse_setter_attrs(&meta, &mut struct_attrs.setter)?;
+                    if struct_attrs.setter.skip.is_some() || struct_attrs.setter.custom.is_some() {
+                        return Err(meta.error("`setter(skip)` and `setter(custom)` can only be used on fields"));
+                    }
+                } else if meta.path.is_ident("pattern") {
+                    let pattern: syn::LitStr = meta.value()?.parse()?;
+                    struct_attrs.pattern = Some(match pattern.value().as_str() {
+                        "mutable" => Pattern::Mutable,
+                        "owned" => Pattern::Owned,
+                        "immutable" => Pattern::Immutable,
+                        _ => return Err(syn::Error::new_spanned(
+                            pattern, "expected `mutable`, `owned` or `immutable`"
+                        )),
+                    });
+                } else if meta.path.is_ident("error") {
+                    let error: syn::LitStr = meta.value()?.parse()?;
+                    struct_attrs.error = Some(error.parse()?);
+                } else if meta.path.is_ident("const") {
+                    struct_attrs.is_const = true;
+                } else if meta.path.is_ident("try_setter") {
+                    struct_attrs.try_setter = true;
+                } else if meta.path.is_ident("no_std") {
+                    struct_attrs.no_std = true;
+                } else if meta.path.is_ident("env_prefix") {
+                    struct_attrs.env_prefix = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("name") {
+                    let builder: syn::LitStr = meta.value()?.parse()?;
+                    struct_attrs.name = Some(builder.parse()?);
+                } else if meta.path.is_ident("vis") {
+                    let vis: syn::LitStr = meta.value()?.parse()?;
+                    struct_attrs.vis = Some(vis.parse()?);
+                } else if meta.path.is_ident("setter_vis") {
+                    let vis: syn::LitStr = meta.value()?.parse()?;
+                    struct_attrs.setter_vis = Some(vis.parse()?);
+                } else if meta.path.is_ident("derive") {
+                    meta.parse_nested_meta(|meta| {
+                        struct_attrs.derives.push(meta.path);
+                        Ok(())
+                    })?;
+                } else if meta.path.is_ident("build_fn") {
+                    meta.parse_nested_meta(|meta| {
+                        if !meta.path.is_ident("name") { return Err(meta.error("expected `name`")); }
+                        let build_fn: syn::LitStr = meta.value()?.parse()?;
+                        struct_attrs.build_fn = Some(build_fn.parse()?);
+                        Ok(())
+                    })?;
+                } else {
+                    return Err(meta.error("unrecognized builder attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        syn::Result::Ok(struct_attrs)
+    };
+
+    // turn a `snake_case` field name into `CamelCase` for type params, without the `r#`
+    // of a raw identifier
+    let to_camel_case = |ident: &syn::Ident| {
+        ident.unraw().to_string().split('_').filter(|s| !s.is_empty()).map(|s| {
+            let mut chars = s.chars();
+            chars.next().map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+        }).collect::<String>()
+    };
+
+    // turn a `CamelCase` variant name into `snake_case` for the builder fn
+    // an acronym stays one word, so `HTTPRequest` is `http_request`
+    let to_snake_case = |ident: &syn::Ident| {
+        let chars: Vec<_> = ident.unraw().to_string().chars().collect();
+        let mut snake = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            if c.is_uppercase() && i > 0 {
+                let prev = chars[i - 1];
+                let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+                if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                    snake.push('_');
+                }
+            }
+            snake.extend(c.to_lowercase());
+        }
+        snake
+    };
+
+    // -=-=- impl derive for Builder -=-=- //
+
+    // Parse the input tokens into a syntax tree
+    let input = parse_macro_input!(input as DeriveInput);
+
+    // ident name for the struct or enum
+    let name = &input.ident;
+
+    // -=-=- Err Check -=-=- //
+
+    // every error found is collected so they can all be reported at once
+    let mut errors: Vec<syn::Error> = Vec::new();
+
+    let struct_attrs = parse_struct_attrs(&input.attrs).unwrap_or_else(|err| {
+        errors.push(err);
+        StructAttrs::default()
+    });
+    let pattern = struct_attrs.pattern.unwrap_or(if struct_attrs.is_const { Pattern::Owned } else { Pattern::Mutable });
+
+    if struct_attrs.typestate && pattern != Pattern::Owned && struct_attrs.pattern.is_some() {
+        errors.push(syn::Error::new_spanned(
+            name, "a typestate builder always uses `builder(pattern = \"owned\")`"
+        ));
+    }
+
+    // a const builder can only do what a `const fn` can, so it takes and returns itself
+    // by value and has nothing to call that isn't `const`
+    let const_error = |t: &dyn quote::ToTokens, what: &str| syn::Error::new_spanned(
+        t, format!("`builder(const)` can not be combined with {}", what)
+    );
+    if struct_attrs.is_const {
+        if struct_attrs.typestate { errors.push(const_error(name, "`typestate`")); }
+        if pattern != Pattern::Owned { errors.push(const_error(name, "`pattern` other than `owned`")); }
+        if struct_attrs.default { errors.push(const_error(name, "`default` on the struct")); }
+        if let Some(ref validate) = struct_attrs.validate { errors.push(const_error(validate, "`validate`")); }
+        if struct_attrs.setter.into == Some(true) { errors.push(const_error(name, "`setter(into)`")); }
+        if struct_attrs.try_setter { errors.push(const_error(name, "`try_setter`")); }
+    }
+
+    // a builder that derives `Deserialize` fills the fields missing from the input with
+    // `Default`, which needs the `serde` feature to generate
+    let deserialize = struct_attrs.derives.iter().find(|path| {
+        path.segments.last().is_some_and(|s| s.ident == "Deserialize")
+    });
+    if let Some(path) = deserialize {
+        if !cfg!(feature = "serde") {
+            errors.push(syn::Error::new_spanned(
+                path, "`builder(derive(Deserialize))` needs the `serde` feature of derive_builder"
+            ));
+        } else if struct_attrs.typestate {
+            errors.push(syn::Error::new_spanned(
+                path, "a typestate builder can not derive `Deserialize`"
+            ));
+        }
+    }
+    let deserialize = deserialize.is_some();
+
+    if let Some(ref prefix) = struct_attrs.env_prefix {
+        if struct_attrs.typestate {
+            errors.push(syn::Error::new_spanned(prefix, "a typestate builder has no `from_env()`"));
+        } else if struct_attrs.no_std {
+            errors.push(syn::Error::new_spanned(prefix, "a `no_std` builder has no `from_env()`"));
+        }
+    }
+
+    if let syn::Data::Enum(ref data) = input.data {
+        // there is no `Default` to take the unset fields of a variant from
+        if struct_attrs.default {
+            errors.push(syn::Error::new_spanned(
+                data.enum_token, "`builder(default)` can only be used on structs"
+            ));
+        }
+        // there is one builder per variant to name
+        if let Some(ref builder) = struct_attrs.name {
+            errors.push(syn::Error::new_spanned(
+                builder, "`builder(name = \"...\")` can only be used on structs"
+            ));
+        }
+        // the options of the builders all go on the enum
+        for attr in data.variants.iter().flat_map(|v| &v.attrs) {
+            if attr.path().is_ident("builder") {
+                errors.push(syn::Error::new_spanned(
+                    attr, "expected `builder(...)` on the enum or the fields of a variant"
+                ));
+            }
+        }
+    }
+
+    // the builders to make as (builder, fn to get it, what `build()` constructs, fields)
+    // a struct gets a `{Name}Builder`, or its `builder(name = "...")`, from `Name::builder()`
+    // and an enum gets a
+    // `{Name}{Variant}Builder` from `Name::{variant}_builder()` for each of its variants
+    let targets: Vec<(syn::Ident, syn::Ident, TokenStream2, &syn::Fields)> = match &input.data {
+        syn::Data::Struct(data) => vec![(
+            struct_attrs.name.clone().unwrap_or_else(|| format_ident!("{}Builder", name)),
+            format_ident!("builder"),
+            quote! { #name },
+            &data.fields,
+        )],
+        syn::Data::Enum(data) => data.variants.iter().map(|variant| {
+            let variant_name = &variant.ident;
+            (
+                format_ident!("{}{}Builder", name, variant_name),
+                format_ident!("{}_builder", to_snake_case(variant_name), span = variant_name.span()),
+                quote! { #name::#variant_name },
+                &variant.fields,
+            )
+        }).collect(),
+        syn::Data::Union(data) => {
+            return syn::Error::new_spanned(data.union_token, "Builder can only be derived for structs and enums")
+                .to_compile_error().into();
+        },
     };
 
-    // check if an attr is a builder
-    // returns (ident: Option<syn::Ident>, err: Option<TokenStream>)
-    // it is a properly formatted builder `if let (Some(_), _) = attr_is_builder(&attr)`
-    let attr_is_builder = |attr: &syn::Attribute| {
-        if let syn::Meta::List(ref list) = attr.meta {
-            if list.path.segments.len() == 1 && list.path.segments[0].ident == "builder" {
-                let mut tokens = list.tokens.to_owned().into_iter();
-                
-                match tokens.next().unwrap() {
-                    TokenTree::Ident(ref i) => if i != "each" { return (None, Some(make_attr_error(list)) ) },
-                    _ => return (None, Some(make_attr_error(list)) ), 
-                } // assert `each``
-
-                match tokens.next().unwrap() {
-                    TokenTree::Punct(ref p) => if p.as_char() != '=' { return (None, Some(make_attr_error(list)) ) },
-                    _ => return (None, Some(make_attr_error(list)) ), 
-                } // assert `=`
-
-                let lit = match tokens.next().unwrap() {
-                    TokenTree::Literal(l) => l,
-                    _ => return (None, Some(make_attr_error(list)) ),
-                }; // assert literal
-
-                match syn::Lit::new(lit) {
-                    syn::Lit::Str(s) => {
-                        return (Some(syn::Ident::new(&s.value(), s.span())), None)
-                    },
-                    _ => return (None, Some(make_attr_error(list)) ),
-                } // assert literal string
-            }
-            return (None, None)
-        };
-        (None, None)
-    };
-    // check if an [attr] has a builder attr
-    // returns (ident: Option<syn::Ident>, err: Option<TokenStream>) if either is Some
-    // it has a properly formatted builder `if let (Some(_), _) = attr_has_builder(&attrs)`
-    let attr_has_builder = |attrs| {
-        for attr in attrs {
-            let (i, e) = attr_is_builder(attr);
-            if i.is_some() || e.is_some() { return (i, e) }
+    // the options of the fields of each builder
+    let mut target_attrs = Vec::new();
+    for (_, _, _, fields) in &targets {
+        if let syn::Fields::Unnamed(fields) = fields {
+            let msg = match input.data {
+                syn::Data::Enum(_) => "expected a variant with named fields",
+                _ => "expected a struct with named fields",
+            };
+            errors.push(syn::Error::new_spanned(fields, msg));
         }
-        (None, None)
-    };
 
-    // -=-=- impl derive for Builder -=-=- //
+        let mut field_attrs = Vec::new();
+        for field in fields.iter() {
+            // check if there is a parser error
+            let mut attrs = parse_field_attrs(field).unwrap_or_else(|err| {
+                errors.push(err);
+                FieldAttrs::default()
+            });
 
-    // Parse the input tokens into a syntax tree
-    let input = parse_macro_input!(input as DeriveInput);
+            // an `each` field needs the item type of the collection, or the key and value of a map
+            let args_len = if ty_is_map(&field.ty) { 2 } else { 1 };
+            if attrs.each.is_some() && get_inner_types(&field.ty).len() < args_len {
+                errors.push(syn::Error::new_spanned(
+                    &field.ty, "expected a collection type like `Vec<T>` or `HashMap<K, V>`"
+                ));
+            }
 
-    // ident names for structs
-    let name = &input.ident;
-    let builder = syn::Ident::new(&format!("{}Builder", name), name.span());
+            // a sub-builder is built in place, it has no setter to set it or default to fall back to
+            if let Some(ref sub_builder) = attrs.sub_builder {
+                if attrs.each.is_some() || attrs.default.is_some() {
+                    errors.push(syn::Error::new_spanned(
+                        &sub_builder.ty, "`sub_builder` can not be combined with `each` or `default`"
+                    ));
+                }
+                if struct_attrs.typestate || pattern == Pattern::Immutable {
+                    errors.push(syn::Error::new_spanned(
+                        &sub_builder.ty, "`sub_builder` needs the mutable or owned builder pattern"
+                    ));
+                }
+            }
 
-    // get the structs fields to operate on.
-    let fields =
-    if let syn::Data::Struct(data) = &input.data {
-        &data.fields
-    } else { panic!() };
+            // only a collection can be appended to and a sub-builder is merged with its own options
+            if attrs.merge == Some(Merge::Append) && attrs.each.is_none() {
+                errors.push(syn::Error::new_spanned(
+                    field, "`merge = \"append\"` needs an `each` collection"
+                ));
+            }
+            if attrs.merge.is_some() && (attrs.skip || attrs.sub_builder.is_some()) {
+                errors.push(syn::Error::new_spanned(
+                    field, "`merge` can not be combined with `skip` or `sub_builder`"
+                ));
+            }
 
-    // -=-=- Err Check -=-=- //
+            // the variable of a field is read into its setter
+            if let Some(FieldEnv::Var(ref env)) = attrs.env {
+                if attrs.skip || attrs.setter.skip == Some(true) || attrs.setter.custom == Some(true) || attrs.sub_builder.is_some() {
+                    errors.push(syn::Error::new_spanned(
+                        env, "`env` can not be combined with `skip`, `setter(custom)` or `sub_builder`"
+                    ));
+                } else if struct_attrs.typestate {
+                    errors.push(syn::Error::new_spanned(
+                        env, "a typestate builder has no `from_env()`"
+                    ));
+                } else if struct_attrs.no_std {
+                    errors.push(syn::Error::new_spanned(
+                        env, "a `no_std` builder has no `from_env()`"
+                    ));
+                }
+            }
 
-    for field in fields {
-        // check if there is a parser error 
-        if let (_, Some(err)) = attr_has_builder(&field.attrs) {
-            return err.into();
-        }
-    }
+            // a `try_<name>()` passes the converted value on to the setter of the field
+            if attrs.try_setter && (
+                attrs.each.is_some() || attrs.sub_builder.is_some() || attrs.skip ||
+                attrs.setter.skip == Some(true) || attrs.setter.custom == Some(true)
+            ) {
+                errors.push(syn::Error::new_spanned(
+                    field, "`try_setter` needs a field with a generated setter and no `each` or `sub_builder`"
+                ));
+            }
 
-    // -=-=- Impl struct -=-=- //
+            // the options that need to call something that isn't `const`
+            if struct_attrs.is_const {
+                if attrs.each.is_some() { errors.push(const_error(field, "`each`")); }
+                if attrs.sub_builder.is_some() { errors.push(const_error(field, "`sub_builder`")); }
+                if attrs.validate.is_some() { errors.push(const_error(field, "`validate`")); }
+                if attrs.setter.into == Some(true) { errors.push(const_error(field, "`setter(into)`")); }
+                if attrs.try_setter { errors.push(const_error(field, "`try_setter`")); }
+                let skipped = attrs.skip || attrs.setter.skip == Some(true);
+                if let Some(FieldDefault::Trait) = attrs.default {
+                    errors.push(const_error(field, "`default` without an expression"));
+                } else if skipped && attrs.default.is_none() {
+                    errors.push(const_error(field, "`skip` without a `default = \"...\"`"));
+                }
+            }
 
-    let impl_fields = fields.iter().map(|f| {
-        let name = &f.ident;
-        if let (Some(_), _) = attr_has_builder(&f.attrs) {
-            if !ty_is_type(&f.ty, "Vec") { panic!("expected 'Vec' fround {:?}", f.ty) }
-            return quote! { #name: vec![] };
-        }
-        quote! { #name: None }
-    });
-    let block_impl = quote! {
-        impl #name {
-            fn builder() -> #builder {
-                #builder { #( #impl_fields ),* }
+            // a field without a setter is left to its default, or to `Default` without one
+            if attrs.skip || attrs.setter.skip == Some(true) {
+                if attrs.skip && (attrs.each.is_some() || attrs.sub_builder.is_some()) {
+                    errors.push(syn::Error::new_spanned(
+                        field, "`skip` can not be combined with `each` or `sub_builder`"
+                    ));
+                }
+                if attrs.default.is_none() && !struct_attrs.default {
+                    attrs.default = Some(FieldDefault::Trait);
+                }
             }
+
+            // a required field of a typestate builder only becomes `Set` through its setter,
+            // which a hand-written one can't do for the state params of the builder
+            if struct_attrs.typestate && attrs.setter.custom == Some(true) && !attrs.skip &&
+                ty_is_option(&field.ty).is_none() && attrs.each.is_none() &&
+                attrs.default.is_none() && !struct_attrs.default
+            {
+                errors.push(syn::Error::new_spanned(
+                    field, "`setter(custom)` on a typestate builder needs a field that is not required, like one with a `default`"
+                ));
+            }
+            field_attrs.push(attrs);
         }
-    };
+        target_attrs.push(field_attrs);
+    }
 
-    // -=-=- define struct Builder -=-=- //
+    // report all the errors together
+    if let Some(err) = errors.into_iter().reduce(|mut err, next| { err.combine(next); err }) {
+        return err.to_compile_error().into();
+    }
 
-    let builder_fields = fields.iter().map(|f| {
-        let name = &f.ident;
-        let ty = &f.ty;
-        if let Some(ty) = ty_is_option(ty) {
-            quote! { #name: std::option::Option<#ty> }
-        } else if let (Some(_), _) = attr_has_builder(&f.attrs) {
-            quote! { #name: #ty }
-        } else {
-            quote! { #name: std::option::Option<#ty> }
-        }
-    });
-    let block_builder = quote! {
-        pub struct #builder {                
-            #( #builder_fields ),*
-        }
+    // the generics of the struct carried over to the builder and its impls
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // the crates the generated code takes its paths from, which are `core` and `alloc`
+    // instead of `std` for `builder(no_std)`
+    let (core, alloc) = if struct_attrs.no_std {
+        (quote! { ::core }, quote! { ::alloc })
+    } else {
+        (quote! { std }, quote! { std })
     };
 
-    // -=-=- impl Builder -=-=- //
+    // generate a builder named `builder` from `fn #builder_fn()` that builds a `#ctor`
+    // out of the `fields` and their options.
+    let expand_builder = |
+        builder: &syn::Ident,
+        builder_fn: &syn::Ident,
+        ctor: &TokenStream2,
+        fields: Vec<(&syn::Field, &FieldAttrs)>,
+    | {
+        let builder_error = format_ident!("{}Error", builder);
+        let const_fn = struct_attrs.is_const.then(|| quote! { const });
+
+        // the builder and its error are `pub` unless `builder(vis = "...")` says otherwise,
+        // the fn to get the builder and the setters are private unless they are given one.
+        let builder_vis = struct_attrs.vis.as_ref().map_or(quote! { pub }, |vis| quote! { #vis });
+        let fn_vis = &struct_attrs.vis;
+        let setter_vis = &struct_attrs.setter_vis;
+        let build_fn = struct_attrs.build_fn.clone().unwrap_or_else(|| format_ident!("build"));
+        let derives = (!struct_attrs.derives.is_empty()).then(|| {
+            let derives = &struct_attrs.derives;
+            quote! { #[derive(#( #derives ),*)] }
+        });
+
+        // a `PhantomData` over the lifetimes and type params of the struct, since the builder
+        // of a variant or with `builder(skip)` fields might not store anything that uses them
+        let markers: Vec<_> = generics.params.iter().filter_map(|param| match param {
+            syn::GenericParam::Type(ty) => { let ident = &ty.ident; Some(quote! { #core::marker::PhantomData<#ident> }) },
+            syn::GenericParam::Lifetime(li) => { let li = &li.lifetime; Some(quote! { &#li () }) },
+            syn::GenericParam::Const(_) => None,
+        }).collect();
+        let serde_skip = deserialize.then(|| quote! { #[serde(skip)] });
+        let (marker_field, marker_value) = if markers.is_empty() { (None, None) } else {(
+            Some(quote! { #serde_skip __marker: #core::marker::PhantomData<(#( #markers, )*)>, }),
+            Some(quote! { __marker: #core::marker::PhantomData, }),
+        )};
+
+
+        // -=-=- define Builder Error -=-=- //
+
+        let block_error = quote! {
+            #[derive(#core::fmt::Debug)]
+            #builder_vis enum #builder_error {
+                UninitializedField(&'static str),
+                Validation(#alloc::string::String),
+                SubBuilder(&'static str, #alloc::string::String),
+                Env(&'static str, #alloc::string::String),
+            }
+
+            impl #core::fmt::Display for #builder_error {
+                fn fmt(&self, f: &mut #core::fmt::Formatter<'_>) -> #core::fmt::Result {
+                    match self {
+                        Self::UninitializedField(field) => #core::write!(f, "{} is not set.", field),
+                        Self::Validation(msg) => #core::write!(f, "{}", msg),
+                        Self::SubBuilder(field, err) => #core::write!(f, "{}.{}", field, err),
+                        Self::Env(var, err) => #core::write!(f, "{} is invalid: {}", var, err),
+                    }
+                }
+            }
+
+            impl #core::error::Error for #builder_error {}
+
+            impl #core::convert::From<#alloc::string::String> for #builder_error {
+                fn from(msg: #alloc::string::String) -> Self {
+                    Self::Validation(msg)
+                }
+            }
+        };
+
+        // the error returned by `build()`, a `builder(error = "...")` has to be `From` the
+        // builder's error.
+        let error = match struct_attrs.error {
+            Some(ref error) => quote! { #error },
+            None => quote! { #builder_error },
+        };
+
+        // -=-=- Build Fields -=-=- //
 
-    // build the Builder functions for setting the full named value for the attr passed.
-    let impl_builder_fields = fields.iter().filter_map(|f| {
-        // field name
-        let name = &f.ident.to_owned().unwrap();
-        // field type
-        let ty = match ty_is_option(&f.ty) {
-            Some(inner_ty) => inner_ty,
-            None => f.ty.to_owned()
+        // the inner type of an `Option` field whose setter takes it without the `Option`,
+        // which is all of them unless they have `builder(setter(strip_option = false))`
+        let stripped_option = |f: &syn::Field, a: &FieldAttrs| {
+            ty_is_option(&f.ty).filter(|_| a.setter.strip_option.or(struct_attrs.setter.strip_option).unwrap_or(true))
         };
 
-        // if attr has `builder(each = "...")` and no name conflict then build setter
-        if let (Some(ref ident), _) = attr_has_builder(&f.attrs) {
-            if ident == name { return None; }
-            // -=-=- //
-            return Some(quote! {
-                fn #name(&mut self, #name: #ty) -> &mut Self {
-                    self.#name = #name;
-                    self
+        // the `let` binding of a field in `build()`. These are in field order so that a
+        // `builder(default = "...")` can use the fields before it.
+        // `value` is the builder's storage for the field and `missing` is used when a
+        // field that has to be set is not.
+        let build_field = |f: &syn::Field, a: &FieldAttrs, value: TokenStream2, missing: TokenStream2| {
+            let name = &f.ident;
+
+            // run the `builder(validate = "...")` of the field on its final value
+            let validate = a.validate.as_ref().map(|validate| quote! {
+                if let #core::result::Result::Err(msg) = #validate(&#name) {
+                    return #core::result::Result::Err(#core::convert::From::from(#builder_error::Validation(msg)));
                 }
             });
-        }
 
-        // else build generic setter
-        Some(quote! {
-            fn #name(&mut self, #name: #ty) -> &mut Self {
-                self.#name = Some(#name);
-                self
+            if a.each.is_some() { return quote! { let #name = #value; #validate }; }
+
+            // a sub-builder's `value` is the result of its `build()`, its error is put under the field
+            if a.sub_builder.is_some() {
+                return quote! {
+                    let #name = match #value {
+                        #core::result::Result::Ok(#name) => #name,
+                        #core::result::Result::Err(err) => return #core::result::Result::Err(#core::convert::From::from(
+                            #builder_error::SubBuilder(stringify!(#name), #alloc::string::ToString::to_string(&err))
+                        )),
+                    };
+                    #validate
+                };
             }
-        })
-    });
 
-    // build the object fields for the `build()` function.
-    let impl_builder_build_fields = fields.iter().map(|f| {
-        let name = &f.ident;
-        if ty_is_option(&f.ty).is_some() || attr_has_builder(&f.attrs).0.is_some() {
+            let is_option = ty_is_option(&f.ty).is_some();
+            let fallback = match a.default {
+                Some(FieldDefault::Trait) => quote! { #core::default::Default::default() },
+                Some(FieldDefault::Expr(ref expr)) => quote! { #expr },
+                None if struct_attrs.default => quote! { #core::mem::take(&mut __default.#name) },
+                None if is_option => quote! { #core::option::Option::None },
+                None => missing,
+            };
+
+            // a `builder(skip)` field is not in the builder and always uses its default
+            if a.skip { return quote! { let #name = #fallback; #validate }; }
+
+            let some = if stripped_option(f, a).is_some() {
+                quote! { #core::option::Option::Some(#name) }
+            } else {
+                quote! { #name }
+            };
             quote! {
-                #name : self.#name.clone()
+                let #name = match #value {
+                    #core::option::Option::Some(#name) => #some,
+                    #core::option::Option::None => #fallback,
+                };
+                #validate
+            }
+        };
+
+        // run the `builder(validate = "...")` of the struct on the builder before anything
+        // else in `build()`. `builder` is the reference to the builder to pass to it.
+        let build_validate = |builder: TokenStream2| {
+            struct_attrs.validate.as_ref().map(|validate| quote! {
+                if let #core::result::Result::Err(msg) = #validate(#builder) {
+                    return #core::result::Result::Err(#core::convert::From::from(#builder_error::Validation(msg)));
+                }
+            })
+        };
+
+        // if an unset field is taken from the `builder(default)` of the struct
+        let from_default = |a: &FieldAttrs| {
+            struct_attrs.default && a.each.is_none() && a.default.is_none() && a.sub_builder.is_none()
+        };
+
+        // the base value for the unset fields with `builder(default)` on the struct. They are
+        // taken out of it with `mem::take` rather than moved, which the struct might not
+        // allow if it implements `Drop`.
+        let build_default = if fields.iter().any(|(_, a)| from_default(a)) {
+            Some(quote! { let mut __default: #name #ty_generics = #core::default::Default::default(); })
+        } else { None };
+
+        // the bounds needed for the defaults in `build()`
+        let default_bounds: Vec<_> = fields.iter().filter_map(|(f, a)| {
+            let ty = &f.ty;
+            if matches!(a.default, Some(FieldDefault::Trait)) || from_default(a) {
+                Some(quote! { #ty: #core::default::Default })
+            } else { None }
+        }).chain(struct_attrs.default.then(|| {
+            quote! { #name #ty_generics: #core::default::Default }
+        })).collect();
+
+        let names: Vec<_> = fields.iter().map(|(f, _)| &f.ident).collect();
+
+        // the fields stored in the builder, which are all but the `builder(skip)` ones
+        let stored: Vec<_> = fields.iter().filter(|(_, a)| !a.skip).collect();
+        let stored_names: Vec<_> = stored.iter().map(|(f, _)| &f.ident).collect();
+
+        // -=-=- Setter Args -=-=- //
+
+        // if a field gets a setter, which it doesn't with `builder(skip)`, `builder(setter(skip))`
+        // or `builder(setter(custom))`
+        let has_setter = |a: &FieldAttrs| {
+            !a.skip && a.setter.skip != Some(true) && a.setter.custom != Some(true)
+        };
+
+        // if a field gets a `try_<name>()` setter
+        let has_try_setter = |a: &FieldAttrs| {
+            (a.try_setter || struct_attrs.try_setter) && has_setter(a) &&
+            a.each.is_none() && a.sub_builder.is_none()
+        };
+
+        // the arg `name` of a setter that takes a `ty`, with `builder(setter(into))` the
+        // setter is generic over anything that is `Into` it instead.
+        // returns (generics, arg type, the arg as a `ty`)
+        let setter_arg = |a: &FieldAttrs, name: &dyn quote::ToTokens, ty: &dyn quote::ToTokens| {
+            if a.setter.into.or(struct_attrs.setter.into).unwrap_or(false) {
+                (
+                    quote! { <__V: #core::convert::Into<#ty>> },
+                    quote! { __V },
+                    quote! { #core::convert::Into::into(#name) },
+                )
+            } else {
+                (quote! {}, quote! { #ty }, quote! { #name })
+            }
+        };
+
+        // the generics, args and the item of the setter named `arg` that adds one item to
+        // the `each` collection of a field. The setter of a map takes the key and the value.
+        let each_setter = |f: &syn::Field, a: &FieldAttrs, arg: &syn::Ident| {
+            let tys = get_inner_types(&f.ty);
+            if !ty_is_map(&f.ty) {
+                let (generics, ty, item) = setter_arg(a, arg, &tys[0]);
+                return (generics, quote! { #arg: #ty }, item);
+            }
+            let (key_ty, value_ty) = (&tys[0], &tys[1]);
+            if a.setter.into.or(struct_attrs.setter.into).unwrap_or(false) {
+                (
+                    quote! { <__K: #core::convert::Into<#key_ty>, __V: #core::convert::Into<#value_ty>> },
+                    quote! { key: __K, value: __V },
+                    quote! { (#core::convert::Into::into(key), #core::convert::Into::into(value)) },
+                )
+            } else {
+                (quote! {}, quote! { key: #key_ty, value: #value_ty }, quote! { (key, value) })
+            }
+        };
+
+        // the name and generics of the `extend_<name>` method of an `each` collection that
+        // adds all the items of an iterator to it
+        let each_extend = |f: &syn::Field| {
+            let name = f.ident.as_ref().unwrap();
+            let tys = get_inner_types(&f.ty);
+            let item = if ty_is_map(&f.ty) {
+                let (key_ty, value_ty) = (&tys[0], &tys[1]);
+                quote! { (#key_ty, #value_ty) }
+            } else {
+                let ty = &tys[0];
+                quote! { #ty }
+            };
+            (format_ident!("extend_{}", name), quote! { <__I: #core::iter::IntoIterator<Item = #item>> })
+        };
+
+        // the type the setter of a field without `each` takes, which the builder stores in
+        // an `Option`. A field with `builder(setter(strip_option = false))` is stored as an
+        // `Option<Option<T>>` so that setting it to `None` is not the same as leaving it unset.
+        let setter_ty = |f: &syn::Field, a: &FieldAttrs| {
+            stripped_option(f, a).unwrap_or_else(|| f.ty.to_owned())
+        };
+
+        // -=-=- From and Getters -=-=- //
+
+        // the builder's storage for each field taken from an existing `value`, only
+        // structs have a single way to take them out. `value` might implement `Drop` so it
+        // can't be moved out of, instead each stored field is read out of it once and the
+        // skipped ones are dropped in place, without running the `Drop` of `value` itself.
+        let from_fields = stored.iter().map(|(f, a)| {
+            let name = &f.ident;
+            let value = quote! { unsafe { #core::ptr::read(&value.#name) } };
+            if a.each.is_some() || stripped_option(f, a).is_some() {
+                quote! { #name: #value }
+            } else if a.sub_builder.is_some() {
+                quote! { #name: #core::convert::From::from(#value) }
+            } else {
+                quote! { #name: #core::option::Option::Some(#value) }
             }
+        });
+        let skipped: Vec<_> = fields.iter().filter(|(_, a)| a.skip).map(|(f, _)| &f.ident).collect();
+        let from_value = if skipped.is_empty() {
+            quote! { let value = #core::mem::ManuallyDrop::new(value); }
         } else {
             quote! {
-                #name : self.#name.clone().ok_or(concat!(stringify!(#name), " is not set."))?
+                let mut value = #core::mem::ManuallyDrop::new(value);
+                #( unsafe { #core::ptr::drop_in_place(&mut value.#skipped) }; )*
+            }
+        };
+        let from_struct = matches!(input.data, syn::Data::Struct(_));
+
+        // the `get_<name>()` to read each field back out of the builder
+        let impl_getters = stored.iter().map(|(f, a)| {
+            let name = &f.ident;
+            let ty = &f.ty;
+            let getter = format_ident!("get_{}", name.as_ref().unwrap());
+            let (ty, get) = if let Some(SubBuilder { ty: ref sub_builder, .. }) = a.sub_builder {
+                (quote! { &#sub_builder }, quote! { &self.#name })
+            } else if a.each.is_some() {
+                (quote! { &#ty }, quote! { &self.#name })
+            } else {
+                let ty = setter_ty(f, a);
+                (quote! { #core::option::Option<&#ty> }, quote! { self.#name.as_ref() })
+            };
+            quote! {
+                #setter_vis fn #getter(&self) -> #ty {
+                    #get
+                }
             }
+        });
+
+        // -=-=- Typestate Builder -=-=- //
+
+        if struct_attrs.typestate {
+            // the fields without a default that need to be set before `build()` exists
+            let is_required = |f: &syn::Field, a: &FieldAttrs| {
+                ty_is_option(&f.ty).is_none() && a.each.is_none() &&
+                a.default.is_none() && !struct_attrs.default
+            };
+            let required: Vec<_> = stored.iter().filter(|(f, a)| is_required(f, a)).collect();
+
+            // one state type param per required field
+            let set = format_ident!("{}Set", builder);
+            let unset = format_ident!("{}Unset", builder);
+            let states: Vec<_> = required.iter().map(|(f, _)| {
+                format_ident!("__{}", to_camel_case(f.ident.as_ref().unwrap()))
+            }).collect();
+            let unsets = states.iter().map(|_| &unset);
+            let sets: Vec<_> = states.iter().map(|_| &set).collect();
+
+            // the struct's own generic params go in front of the states, without defaults
+            // since the states that follow them can't have one.
+            let params: Vec<_> = input.generics.params.iter().map(|param| {
+                let mut param = param.to_owned();
+                match param {
+                    syn::GenericParam::Type(ref mut ty) => { ty.eq_token = None; ty.default = None; },
+                    syn::GenericParam::Const(ref mut c) => { c.eq_token = None; c.default = None; },
+                    syn::GenericParam::Lifetime(_) => {},
+                }
+                param
+            }).collect();
+            // and the arguments to name the builder type with them
+            let args: Vec<_> = input.generics.params.iter().map(|param| match param {
+                syn::GenericParam::Type(ty) => { let ident = &ty.ident; quote! { #ident } },
+                syn::GenericParam::Const(c) => { let ident = &c.ident; quote! { #ident } },
+                syn::GenericParam::Lifetime(li) => { let li = &li.lifetime; quote! { #li } },
+            }).collect();
+
+            let impl_fields = stored.iter().map(|(f, a)| {
+                let name = &f.ident;
+                if a.each.is_some() {
+                    return quote! { #name: #core::default::Default::default() };
+                }
+                quote! { #name: #core::option::Option::None }
+            });
+            let block_impl = quote! {
+                impl #impl_generics #name #ty_generics #where_clause {
+                    #fn_vis fn #builder_fn() -> #builder<#( #args, )* #( #unsets ),*> {
+                        #builder {
+                            #( #impl_fields, )*
+                            __state: #core::marker::PhantomData,
+                        }
+                    }
+                }
+            };
+
+            let builder_fields = stored.iter().map(|(f, a)| {
+                let name = &f.ident;
+                let ty = &f.ty;
+                if a.each.is_some() {
+                    quote! { #name: #ty }
+                } else {
+                    let ty = setter_ty(f, a);
+                    quote! { #name: #core::option::Option<#ty> }
+                }
+            });
+            let block_builder = quote! {
+                #derives
+                #builder_vis struct #set;
+                #derives
+                #builder_vis struct #unset;
+
+                #derives
+                #builder_vis struct #builder<#( #params, )* #( #states ),*> #where_clause {
+                    #( #builder_fields, )*
+                    __state: #core::marker::PhantomData<(#( #markers, )* #( #states, )*)>,
+                }
+            };
+
+            // the setters for the required fields move the builder into the `Set` state
+            let impl_builder_required = required.iter().enumerate().filter(|(_, (_, a))| has_setter(a)).map(|(i, (f, a))| {
+                let name = &f.ident;
+                let (generics, ty, value) = setter_arg(a, name, &f.ty);
+                let states_out: Vec<_> = states.iter().enumerate().map(|(j, state)| {
+                    if i == j { &set } else { state }
+                }).collect();
+                let others = stored_names.iter().filter(|other| **other != name);
+                let try_setter = has_try_setter(a).then(|| {
+                    let try_name = format_ident!("try_{}", name.as_ref().unwrap());
+                    let ty = &f.ty;
+                    quote! {
+                        #setter_vis fn #try_name<__T: #core::convert::TryInto<#ty>>(self, #name: __T)
+                            -> #core::result::Result<#builder<#( #args, )* #( #states_out ),*>, __T::Error>
+                        {
+                            #core::result::Result::Ok(self.#name(#core::convert::TryInto::try_into(#name)?))
+                        }
+                    }
+                });
+                quote! {
+                    impl<#( #params, )* #( #states ),*> #builder<#( #args, )* #( #states ),*> #where_clause {
+                        #setter_vis fn #name #generics(self, #name: #ty) -> #builder<#( #args, )* #( #states_out ),*> {
+                            #builder {
+                                #name: #core::option::Option::Some(#value),
+                                #( #others: self.#others, )*
+                                __state: #core::marker::PhantomData,
+                            }
+                        }
+                        #try_setter
+                    }
+                }
+            });
+
+            // the `try_<name>()` setters for the other fields
+            let impl_try_setters = stored.iter().filter(|(f, a)| !is_required(f, a) && has_try_setter(a)).map(|(f, a)| {
+                let name = &f.ident;
+                let try_name = format_ident!("try_{}", name.as_ref().unwrap());
+                let ty = setter_ty(f, a);
+                quote! {
+                    #setter_vis fn #try_name<__T: #core::convert::TryInto<#ty>>(self, #name: __T)
+                        -> #core::result::Result<Self, __T::Error>
+                    {
+                        #core::result::Result::Ok(self.#name(#core::convert::TryInto::try_into(#name)?))
+                    }
+                }
+            });
+
+            // the setters for the other fields keep the current state
+            let impl_builder_fields = stored.iter().filter(|(f, a)| !is_required(f, a) && has_setter(a)).filter_map(|(f, a)| {
+                let name = &f.ident;
+                if let Some(ref ident) = a.each {
+                    if Some(ident) == name.as_ref() { return None; }
+                    let (generics, ty, value) = setter_arg(a, name, &f.ty);
+                    return Some(quote! {
+                        #setter_vis fn #name #generics(mut self, #name: #ty) -> Self {
+                            self.#name = #value;
+                            self
+                        }
+                    });
+                }
+                let (generics, ty, value) = setter_arg(a, name, &setter_ty(f, a));
+                Some(quote! {
+                    #setter_vis fn #name #generics(mut self, #name: #ty) -> Self {
+                        self.#name = #core::option::Option::Some(#value);
+                        self
+                    }
+                })
+            });
+            let impl_extend_methods = stored.iter().filter(|(_, a)| has_setter(a)).filter_map(|(f, a)| {
+                if let Some(ref arg) = a.each {
+                    let name = &f.ident;
+                    let (generics, args, item) = each_setter(f, a, arg);
+                    let (extend, extend_generics) = each_extend(f);
+                    return Some(quote! {
+                        #setter_vis fn #arg #generics(mut self, #args) -> Self {
+                            #core::iter::Extend::extend(&mut self.#name, #core::iter::once(#item));
+                            self
+                        }
+
+                        #setter_vis fn #extend #extend_generics(mut self, #name: __I) -> Self {
+                            #core::iter::Extend::extend(&mut self.#name, #name);
+                            self
+                        }
+                    });
+                }
+                None
+            });
+
+            // `build()` only exists once every required field is `Set`
+            let impl_builder_build_fields = fields.iter().map(|(f, a)| {
+                let name = &f.ident;
+                build_field(f, a, quote! { self.#name }, quote! { #core::unreachable!() })
+            });
+            let build_validate = build_validate(quote! { &self });
+
+            // it can only fail when there is something to validate
+            let validates = struct_attrs.validate.is_some() || fields.iter().any(|(_, a)| a.validate.is_some());
+            let (build_output, build_result) = if validates {
+                (
+                    quote! { #core::result::Result<#name #ty_generics, #error> },
+                    quote! { #core::result::Result::Ok(#ctor { #( #names ),* }) },
+                )
+            } else {
+                (quote! { #name #ty_generics }, quote! { #ctor { #( #names ),* } })
+            };
+
+            let block_impl_builder = quote! {
+                #( #impl_builder_required )*
+
+                impl<#( #params, )* #( #states ),*> #builder<#( #args, )* #( #states ),*> #where_clause {
+                    #( #impl_builder_fields )*
+                    #( #impl_try_setters )*
+                    #( #impl_extend_methods )*
+                    #( #impl_getters )*
+                }
+
+                impl<#( #params ),*> #builder<#( #args, )* #( #sets ),*> #where_clause {
+                    pub fn #build_fn(self) -> #build_output
+                    where #( #default_bounds ),*
+                    {
+                        #build_validate
+                        #build_default
+                        #( #impl_builder_build_fields )*
+                        #build_result
+                    }
+                }
+            };
+
+            // a builder from an existing struct has every field set
+            let block_from = from_struct.then(|| quote! {
+                impl #impl_generics #core::convert::From<#name #ty_generics> for #builder<#( #args, )* #( #sets ),*> #where_clause {
+                    fn from(value: #name #ty_generics) -> Self {
+                        #from_value
+                        #builder {
+                            #( #from_fields, )*
+                            __state: #core::marker::PhantomData,
+                        }
+                    }
+                }
+            });
+
+            let expanded = quote! {
+                #block_impl
+                #block_builder
+                #block_error
+                #block_impl_builder
+                #block_from
+            };
+            return expanded;
         }
-    });
-    
-    // build the extenc methods for all the `builder(each = "...")` attributes.
-    let impl_extend_methods = fields.iter().filter_map(|f| {
-        for attr in &f.attrs {
-            if let (Some(arg), _) = attr_is_builder(&attr) {
-                if !ty_is_type(&f.ty, "Vec") { panic!("expected 'Vec' fround {:?}", f.ty) }
+
+        // -=-=- Impl struct -=-=- //
+
+        let impl_fields = stored.iter().map(|(f, a)| {
+            let name = &f.ident;
+            let ty = &f.ty;
+            if a.each.is_some() {
+                return quote! { #name: #core::default::Default::default() };
+            } else if a.sub_builder.is_some() {
+                return quote! { #name: <#ty>::builder() };
+            }
+            quote! { #name: #core::option::Option::None }
+        });
+        let block_impl = quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #fn_vis #const_fn fn #builder_fn() -> #builder #ty_generics {
+                    #builder { #( #impl_fields, )* #marker_value }
+                }
+            }
+        };
+
+        // -=-=- define struct Builder -=-=- //
+
+        // the fields missing from the input to `Deserialize` are left unset
+        let serde_default = deserialize.then(|| quote! { #[serde(default)] });
+
+        let builder_fields = stored.iter().map(|(f, a)| {
+            let name = &f.ident;
+            let ty = &f.ty;
+            // a field without a setter can't be set from the input either
+            let serde_skip = (!has_setter(a)).then_some(&serde_skip);
+            if let Some(SubBuilder { ty: ref sub_builder, .. }) = a.sub_builder {
+                quote! { #serde_skip #name: #sub_builder }
+            } else if a.each.is_some() {
+                quote! { #serde_skip #name: #ty }
+            } else {
+                let ty = setter_ty(f, a);
+                quote! { #serde_skip #name: #core::option::Option<#ty> }
+            }
+        });
+        let block_builder = quote! {
+            #derives
+            #serde_default
+            #builder_vis struct #builder #generics #where_clause {
+                #( #builder_fields, )*
+                #marker_field
+            }
+        };
+
+        // -=-=- impl Builder -=-=- //
+
+        // the types and names of the fields to clone a builder with the immutable pattern
+        let builder_tys: Vec<_> = stored.iter().map(|(f, _)| &f.ty).collect();
+
+        // wrap the body of a setter in the signature for the builder's pattern.
+        // `method` and `args` are the name and arguments of the setter and `body` sets the
+        // field on the builder it is given.
+        let make_setter = |method: TokenStream2, args: TokenStream2, body: &dyn Fn(TokenStream2) -> TokenStream2| {
+            match pattern {
+                Pattern::Mutable => {
+                    let body = body(quote! { self });
+                    quote! {
+                        #setter_vis fn #method(&mut self, #args) -> &mut Self {
+                            #body
+                            self
+                        }
+                    }
+                },
+                Pattern::Owned => {
+                    let body = body(quote! { self });
+                    quote! {
+                        #setter_vis #const_fn fn #method(mut self, #args) -> Self {
+                            #body
+                            self
+                        }
+                    }
+                },
+                Pattern::Immutable => {
+                    let body = body(quote! { __builder });
+                    quote! {
+                        #setter_vis fn #method(&self, #args) -> Self
+                        where #( #builder_tys: #core::clone::Clone ),*
+                        {
+                            let mut __builder = Self {
+                                #( #stored_names: #core::clone::Clone::clone(&self.#stored_names), )*
+                                #marker_value
+                            };
+                            #body
+                            __builder
+                        }
+                    }
+                },
+            }
+        };
+
+        // build the Builder functions for setting the full named value for the attr passed.
+        let impl_builder_fields = stored.iter().filter(|(_, a)| has_setter(a)).filter_map(|(f, a)| {
+            // field name
+            let name = &f.ident;
+
+            // a `builder(sub_builder)` field hands out its builder to set the fields on instead
+            if let Some(SubBuilder { ty: ref sub_builder, .. }) = a.sub_builder {
+                return Some(quote! {
+                    #setter_vis fn #name(&mut self) -> &mut #sub_builder {
+                        &mut self.#name
+                    }
+                });
+            }
+
+            // if attr has `builder(each = "...")` and no name conflict then build setter
+            if let Some(ref ident) = a.each {
+                if Some(ident) == name.as_ref() { return None; }
+                // -=-=- //
+                let (generics, ty, value) = setter_arg(a, name, &f.ty);
+                return Some(make_setter(quote! { #name #generics }, quote! { #name: #ty }, &|this| quote! {
+                    #this.#name = #value;
+                }));
+            }
+
+            // else build generic setter
+            let (generics, ty, value) = setter_arg(a, name, &setter_ty(f, a));
+            Some(make_setter(quote! { #name #generics }, quote! { #name: #ty }, &|this| quote! {
+                #this.#name = #core::option::Option::Some(#value);
+            }))
+        });
+
+        // build the `try_<name>()` setters that convert the value with `TryInto` first, and
+        // return its error if it fails
+        let impl_try_setters = stored.iter().filter(|(_, a)| has_try_setter(a)).map(|(f, a)| {
+            let name = &f.ident;
+            let try_name = format_ident!("try_{}", name.as_ref().unwrap());
+            let ty = setter_ty(f, a);
+            let (this, output, bounds) = match pattern {
+                Pattern::Mutable => (quote! { &mut self }, quote! { &mut Self }, None),
+                Pattern::Owned => (quote! { self }, quote! { Self }, None),
+                Pattern::Immutable => (quote! { &self }, quote! { Self }, Some(quote! {
+                    where #( #builder_tys: #core::clone::Clone ),*
+                })),
+            };
+            quote! {
+                #setter_vis fn #try_name<__T: #core::convert::TryInto<#ty>>(#this, #name: __T)
+                    -> #core::result::Result<#output, __T::Error>
+                #bounds
+                {
+                    #core::result::Result::Ok(self.#name(#core::convert::TryInto::try_into(#name)?))
+                }
+            }
+        });
+
+        // build the object fields for the `build()` function.
+        let impl_builder_build_fields = fields.iter().map(|(f, a)| {
+            let name = &f.ident;
+            let value = match pattern {
+                _ if a.sub_builder.is_some() => {
+                    let build_fn = a.sub_builder.as_ref().map(|sub_builder| &sub_builder.build_fn);
+                    quote! { self.#name.#build_fn() }
+                },
+                Pattern::Owned => quote! { self.#name },
+                _ => quote! { self.#name.clone() },
+            };
+            let missing = if struct_attrs.is_const {
+                quote! { #core::panic!(concat!(stringify!(#name), " is not set.")) }
+            } else {
+                quote! {
+                    return #core::result::Result::Err(#core::convert::From::from(
+                        #builder_error::UninitializedField(stringify!(#name))
+                    ))
+                }
+            };
+            build_field(f, a, value, missing)
+        });
+
+        // build the extend methods for all the `builder(each = "...")` attributes.
+        let impl_extend_methods = stored.iter().filter(|(_, a)| has_setter(a)).filter_map(|(f, a)| {
+            if let Some(ref arg) = a.each {
                 // -=-=- //
                 let name = &f.ident;
-                let ty = get_inner_type(&f.ty, 0);
+                let (generics, args, item) = each_setter(f, a, arg);
+                let (extend, extend_generics) = each_extend(f);
                 // -=-=- //
-                return Some(quote! {
-                    fn #arg(&mut self, #arg: #ty) -> &mut Self {
-                        self.#name.push(#arg);
-                        self
-                    }
+                let each = make_setter(quote! { #arg #generics }, args, &|this| quote! {
+                    #core::iter::Extend::extend(&mut #this.#name, #core::iter::once(#item));
                 });
+                let extend = make_setter(quote! { #extend #extend_generics }, quote! { #name: __I }, &|this| quote! {
+                    #core::iter::Extend::extend(&mut #this.#name, #name);
+                });
+                return Some(quote! { #each #extend });
             }
-        }
-        None
-    });
+            None
+        });
 
-    // the `build()` function
-    let impl_builder_build = quote! {
-        pub fn build(&self) -> std::result::Result<#name, std::boxed::Box<dyn std::error::Error>> {
-            std::result::Result::Ok(#name{ #( #impl_builder_build_fields ),* })
-    }
-    };
+        // the `merge()` of another builder into this one. A set field of `other` replaces
+        // the field unless it has `builder(merge = "keep")`, and the items of an `each`
+        // collection are appended unless it has `builder(merge = "replace")`. Sub-builders
+        // are merged in turn.
+        let impl_merge_fields = stored.iter().map(|(f, a)| {
+            let name = &f.ident;
+            let merge = a.merge.unwrap_or(if a.each.is_some() { Merge::Append } else { Merge::Replace });
+            let core = &core;
+            move |this: &TokenStream2| match merge {
+                _ if a.sub_builder.is_some() => quote! { #this.#name.merge(other.#name); },
+                Merge::Append => quote! { #core::iter::Extend::extend(&mut #this.#name, other.#name); },
+                Merge::Replace if a.each.is_some() => quote! {
+                    if !other.#name.is_empty() { #this.#name = other.#name; }
+                },
+                Merge::Keep if a.each.is_some() => quote! {
+                    if #this.#name.is_empty() { #this.#name = other.#name; }
+                },
+                Merge::Replace => quote! {
+                    if other.#name.is_some() { #this.#name = other.#name; }
+                },
+                Merge::Keep => quote! {
+                    if #this.#name.is_none() { #this.#name = other.#name; }
+                },
+            }
+        }).collect::<Vec<_>>();
+        let impl_merge = (!struct_attrs.is_const).then(|| make_setter(quote! { merge }, quote! { other: Self }, &|this| {
+            let merges = impl_merge_fields.iter().map(|merge| merge(&this));
+            quote! { #( #merges )* }
+        }));
+
+        // the `from_env()` that sets each field from its environment variable, either its
+        // `builder(env = "...")` or the upper case name of the field after the
+        // `builder(env_prefix = "...")` of the struct. The values are parsed with
+        // `FromStr` and an `each` collection is split on its `builder(env_separator = "...")`.
+        let env_fields: Vec<_> = stored.iter().filter(|(_, a)| {
+            has_setter(a) && a.sub_builder.is_none()
+        }).filter_map(|(f, a)| {
+            let name = &f.ident;
+            let var = match (&a.env, &struct_attrs.env_prefix) {
+                (Some(FieldEnv::Var(var)), _) => var.value(),
+                (Some(FieldEnv::Skip), _) => return None,
+                (None, Some(prefix)) => format!("{}{}", prefix.value(), name.as_ref().unwrap().unraw().to_string().to_uppercase()),
+                (None, None) => return None,
+            };
+            let env_error = |err: TokenStream2| quote! {
+                return #core::result::Result::Err(#core::convert::From::from(#builder_error::Env(#var, #err)))
+            };
+            let parse = |ty: &syn::Type, value: TokenStream2| {
+                let env_error = env_error(quote! { #alloc::string::ToString::to_string(&err) });
+                quote! {
+                    match <#ty as #core::str::FromStr>::from_str(#value) {
+                        #core::result::Result::Ok(value) => value,
+                        #core::result::Result::Err(err) => #env_error,
+                    }
+                }
+            };
 
-    // impl the whole builder
-    let block_impl_builder = quote! {
-        impl #builder {
-            #( #impl_builder_fields )*
-            #( #impl_extend_methods )*
+            let (tys, set) = if a.each.is_some() {
+                let tys = get_inner_types(&f.ty);
+                let separator = a.env_separator.as_ref().map_or(",".to_owned(), |s| s.value());
+                let item = if ty_is_map(&f.ty) {
+                    let (key, value) = (parse(&tys[0], quote! { key }), parse(&tys[1], quote! { value }));
+                    let env_error = env_error(quote! { #alloc::string::ToString::to_string("expected `key=value`") });
+                    quote! {{
+                        let (key, value) = match item.split_once('=') {
+                            #core::option::Option::Some(pair) => pair,
+                            #core::option::Option::None => #env_error,
+                        };
+                        (#key, #value)
+                    }}
+                } else {
+                    parse(&tys[0], quote! { item })
+                };
+                (tys, quote! {
+                    for item in value.split(#separator).map(|item| item.trim()).filter(|item| !item.is_empty()) {
+                        #core::iter::Extend::extend(&mut __builder.#name, #core::iter::once(#item));
+                    }
+                })
+            } else {
+                let ty = ty_is_option(&f.ty).unwrap_or_else(|| f.ty.to_owned());
+                let mut value = parse(&ty, quote! { &value });
+                if ty_is_option(&f.ty).is_some() && stripped_option(f, a).is_none() {
+                    value = quote! { #core::option::Option::Some(#value) };
+                }
+                (vec![ty], quote! { __builder.#name = #core::option::Option::Some(#value); })
+            };
 
-            #impl_builder_build
-        }
+            let env_error = env_error(quote! { #alloc::string::ToString::to_string(&err) });
+            Some((tys, quote! {
+                match std::env::var(#var) {
+                    #core::result::Result::Ok(value) => { #set }
+                    #core::result::Result::Err(std::env::VarError::NotPresent) => {}
+                    #core::result::Result::Err(err) => #env_error,
+                }
+            }))
+        }).collect();
+        let impl_from_env = (!env_fields.is_empty()).then(|| {
+            let tys = env_fields.iter().flat_map(|(tys, _)| tys);
+            let reads = env_fields.iter().map(|(_, read)| read);
+            quote! {
+                #fn_vis fn from_env() -> #core::result::Result<Self, #error>
+                where #( #tys: #core::str::FromStr, <#tys as #core::str::FromStr>::Err: #core::fmt::Display ),*
+                {
+                    let mut __builder = <#name #ty_generics>::#builder_fn();
+                    #( #reads )*
+                    #core::result::Result::Ok(__builder)
+                }
+            }
+        });
+
+        // the `build()` function
+        // unless the builder is owned every field is cloned out of it so their types need
+        // to be `Clone`, except for the sub-builders which are built in place
+        let (build_self, build_validate, field_tys) = match pattern {
+            Pattern::Owned => (quote! { self }, build_validate(quote! { &self }), vec![]),
+            _ => (quote! { &self }, build_validate(quote! { self }), stored.iter().filter_map(|(f, a)| {
+                a.sub_builder.is_none().then_some(&f.ty)
+            }).collect()),
+        };
+        // a const `build()` panics on a missing field since it has no error to return
+        let impl_builder_build = if struct_attrs.is_const {
+            quote! {
+                pub const fn #build_fn(self) -> #name #ty_generics {
+                    #( #impl_builder_build_fields )*
+                    #ctor { #( #names ),* }
+                }
+            }
+        } else {
+            quote! {
+                pub fn #build_fn(#build_self) -> #core::result::Result<#name #ty_generics, #error>
+                where #( #field_tys: #core::clone::Clone, )* #( #default_bounds ),*
+                {
+                    #build_validate
+                    #build_default
+                    #( #impl_builder_build_fields )*
+                    #core::result::Result::Ok(#ctor { #( #names ),* })
+                }
+            }
+        };
+
+        // impl the whole builder
+        let block_impl_builder = quote! {
+            impl #impl_generics #builder #ty_generics #where_clause {
+                #( #impl_builder_fields )*
+                #( #impl_try_setters )*
+                #( #impl_extend_methods )*
+                #( #impl_getters )*
+                #impl_merge
+                #impl_from_env
+
+                #impl_builder_build
+            }
+        };
+
+        // -=-=- impl From -=-=- //
+
+        let block_from = from_struct.then(|| quote! {
+            impl #impl_generics #core::convert::From<#name #ty_generics> for #builder #ty_generics #where_clause {
+                fn from(value: #name #ty_generics) -> Self {
+                    #from_value
+                    #builder { #( #from_fields, )* #marker_value }
+                }
+            }
+        });
+
+        // -=-=- impl Default -=-=- //
+
+        // the empty builder for `#[serde(default)]`
+        let block_default = deserialize.then(|| quote! {
+            impl #impl_generics #core::default::Default for #builder #ty_generics #where_clause {
+                fn default() -> Self {
+                    <#name #ty_generics>::#builder_fn()
+                }
+            }
+        });
+
+        // Build the output for this builder using the other blocks
+        let expanded = quote! {
+            #block_impl
+            #block_builder
+            #block_error
+            #block_impl_builder
+            #block_from
+            #block_default
+        };
+
+        expanded
     };
 
     // -=-=- Output -=-=- //
 
-    // Build the output using the other blocks
-    let expanded = quote! {
-        #block_impl  
-        #block_builder
-        #block_impl_builder
-    };
+    // Build the output from all the builders
+    let expanded = targets.iter().zip(&target_attrs).map(|((builder, builder_fn, ctor, fields), field_attrs)| {
+        expand_builder(builder, builder_fn, ctor, fields.iter().zip(field_attrs).collect())
+    });
 
     // Hand the output tokens back to the compiler
-    expanded.into()
+    quote! { #( #expanded )* }.into()
+}
+
+// -=-=- Attribute Types -=-=- //
+
+// the options parsed from the `#[builder(...)]` attrs on the struct
+#[derive(Default)]
+struct StructAttrs {
+    // `builder(typestate)`
+    typestate: bool,
+    // `builder(default)`
+    default: bool,
+    // `builder(error = "...")`
+    error: Option<syn::Type>,
+    // `builder(validate = "...")`
+    validate: Option<syn::Path>,
+    // `builder(pattern = "...")`
+    pattern: Option<Pattern>,
+    // `builder(setter(...))` for all the fields
+    setter: SetterAttrs,
+    // `builder(name = "...")`
+    name: Option<syn::Ident>,
+    // `builder(vis = "...")`
+    vis: Option<syn::Visibility>,
+    // `builder(setter_vis = "...")`
+    setter_vis: Option<syn::Visibility>,
+    // `builder(derive(...))`
+    derives: Vec<syn::Path>,
+    // `builder(build_fn(name = "..."))`
+    build_fn: Option<syn::Ident>,
+    // `builder(env_prefix = "...")`
+    env_prefix: Option<syn::LitStr>,
+    // `builder(no_std)`
+    no_std: bool,
+    // `builder(const)`
+    is_const: bool,
+    // `builder(try_setter)` for all the fields
+    try_setter: bool,
+}
+
+// how the setters and `build()` take the builder
+#[derive(Clone, Copy, PartialEq)]
+enum Pattern {
+    // `&mut self`, the default
+    Mutable,
+    // `self`
+    Owned,
+    // `&self`, setters return a new builder
+    Immutable,
+}
+
+// the options parsed from the `#[builder(...)]` attrs on a field
+#[derive(Default)]
+struct FieldAttrs {
+    // `builder(each = "...")`
+    each: Option<syn::Ident>,
+    // `builder(default)` or `builder(default = "...")`
+    default: Option<FieldDefault>,
+    // `builder(validate = "...")`
+    validate: Option<syn::Path>,
+    // `builder(setter(...))`
+    setter: SetterAttrs,
+    // `builder(sub_builder)`, `builder(sub_builder = "...")` or `builder(sub_builder(...))`
+    sub_builder: Option<SubBuilder>,
+    // `builder(skip)`
+    skip: bool,
+    // `builder(merge = "...")`
+    merge: Option<Merge>,
+    // `builder(try_setter)`
+    try_setter: bool,
+    // `builder(env = "...")` or `builder(env = false)`
+    env: Option<FieldEnv>,
+    // `builder(env_separator = "...")`
+    env_separator: Option<syn::LitStr>,
+}
+
+// the builder of a `builder(sub_builder)` field
+struct SubBuilder {
+    // the type of the builder, `{Type}Builder` unless it is named
+    ty: syn::Type,
+    // the `build()` of the builder, renamed with `build_fn = "..."`
+    build_fn: syn::Ident,
+}
+
+// how `merge()` combines a field with the one of the other builder
+#[derive(Clone, Copy, PartialEq)]
+enum Merge {
+    // the set value of the other builder wins, the default without `each`
+    Replace,
+    // the items of the other builder are added, the default with `each`
+    Append,
+    // the value of this builder wins if it is set
+    Keep,
+}
+
+// where `from_env()` reads a field from
+enum FieldEnv {
+    // `builder(env = "...")` names the variable
+    Var(syn::LitStr),
+    // `builder(env = false)` leaves the field out
+    Skip,
+}
+
+// the options of a `builder(setter(...))` attr, `None` when they are not given
+#[derive(Default)]
+struct SetterAttrs {
+    // `setter(into)`
+    into: Option<bool>,
+    // `setter(strip_option)`
+    strip_option: Option<bool>,
+    // `setter(skip)`
+    skip: Option<bool>,
+    // `setter(custom)`
+    custom: Option<bool>,
+}
+
+// where the value of an unset field comes from
+enum FieldDefault {
+    // `builder(default)` uses `Default::default()`
+    Trait,
+    // `builder(default = "...")` uses the expression
+    Expr(syn::Expr),
 }
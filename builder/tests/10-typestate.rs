@@ -0,0 +1,52 @@
+// With #[builder(typestate)] on the struct the builder tracks which of the
+// required fields have been set in its type, so `build()` can only be called
+// once all of them are set and returns the struct directly instead of a
+// Result.
+//
+// Optional and repeated fields can be set in any state and the setters of the
+// required fields can be called in any order. A raw identifier can name a
+// required field as well.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Token {
+    r#type: String,
+}
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .env(vec![])
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .arg("--release".to_owned())
+        .build();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert!(command.env.is_empty());
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+
+    let command = Command::builder()
+        .env(vec!["RUST_LOG=debug".to_owned()])
+        .executable("rustc".to_owned())
+        .build();
+
+    assert!(command.args.is_empty());
+    assert!(command.current_dir.is_none());
+
+    let token = Token::builder().r#type("ident".to_owned()).build();
+    assert_eq!(token.r#type, "ident");
+}
@@ -0,0 +1,20 @@
+// A typestate builder turns a missing required field into a compile error: the
+// `build()` method only exists on the builder once every required field is in
+// the `Set` state.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let _command = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .build();
+}
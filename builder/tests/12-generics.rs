@@ -0,0 +1,71 @@
+// The builder carries the generics of the struct through to the builder type
+// and all of its impl blocks, including lifetimes, const generics, defaulted
+// type params and where-clauses.
+//
+// Since `build()` clones the fields out of the builder it is only available
+// when the field types are `Clone`, so the codec here has to derive it.
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+pub trait Codec {
+    fn encode(&self) -> Vec<u8>;
+}
+
+#[derive(Clone)]
+pub struct Json;
+
+impl Codec for Json {
+    fn encode(&self) -> Vec<u8> {
+        b"{}".to_vec()
+    }
+}
+
+#[derive(Builder)]
+pub struct Request<'a, T: Codec, const N: usize, E = ()>
+where
+    E: Debug,
+{
+    path: &'a str,
+    codec: T,
+    #[builder(each = "header")]
+    headers: Vec<[&'a str; N]>,
+    extra: Option<E>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Response<'a, T: Codec, E = ()>
+where
+    E: Debug,
+{
+    body: &'a [u8],
+    codec: T,
+    extra: Option<E>,
+}
+
+fn main() {
+    let path = String::from("/index.html");
+
+    let request: Request<Json, 2> = Request::builder()
+        .path(&path)
+        .codec(Json)
+        .header(["Accept", "*/*"])
+        .build()
+        .unwrap();
+
+    assert_eq!(request.path, "/index.html");
+    assert_eq!(request.codec.encode(), b"{}");
+    assert_eq!(request.headers, vec![["Accept", "*/*"]]);
+    assert!(request.extra.is_none());
+
+    let body = request.codec.encode();
+    let response = Response::builder()
+        .codec(Json)
+        .extra("cached")
+        .body(&body)
+        .build();
+
+    assert_eq!(response.body, b"{}");
+    assert_eq!(response.extra, Some("cached"));
+}
@@ -0,0 +1,108 @@
+// Fields with #[builder(default)] fall back to `Default::default()` when they
+// are not set and fields with #[builder(default = "...")] to the expression,
+// which can refer to the fields declared before it. With #[builder(default)]
+// on the struct, the struct's own `Default` impl fills in every other unset
+// field, which works for a struct that implements `Drop` as well.
+//
+// A typestate builder doesn't require the fields with a default either.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    args: Vec<String>,
+    #[builder(default = "vec![format!(\"CARGO={}\", executable)]")]
+    env: Vec<String>,
+    #[builder(default = "Some(\"..\".to_owned())")]
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(default)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(default = "port == 443")]
+    tls: bool,
+    #[builder(each = "alias")]
+    aliases: Vec<String>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server {
+            host: "localhost".to_owned(),
+            port: 80,
+            tls: false,
+            aliases: vec!["default".to_owned()],
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(default)]
+pub struct Connection {
+    host: String,
+    port: u16,
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Connection { host: "localhost".to_owned(), port: 5432 }
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {}
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Retry {
+    attempts: u32,
+    #[builder(default = "attempts * 100")]
+    backoff_ms: u32,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+
+    assert!(command.args.is_empty());
+    assert_eq!(command.env, vec!["CARGO=cargo"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec!["build".to_owned()])
+        .env(vec![])
+        .current_dir("/".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, vec!["build"]);
+    assert!(command.env.is_empty());
+    assert_eq!(command.current_dir.as_deref(), Some("/"));
+
+    assert!(Command::builder().build().is_err());
+
+    let server = Server::builder().port(443).build().unwrap();
+
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 443);
+    assert!(server.tls);
+    assert!(server.aliases.is_empty());
+
+    let connection = Connection::builder().port(6432).build().unwrap();
+
+    assert_eq!(connection.host, "localhost");
+    assert_eq!(connection.port, 6432);
+
+    let retry = Retry::builder().attempts(3).build();
+
+    assert_eq!(retry.backoff_ms, 300);
+}
@@ -0,0 +1,55 @@
+// The builder's `build()` returns a generated `{Name}BuilderError` enum so the
+// caller can tell which field was missing instead of getting a boxed string.
+// It implements Display and Error and converts from a String as a validation
+// failure.
+//
+// With #[builder(error = "...")] on the struct, `build()` returns the given
+// error type instead which is converted from the generated one.
+
+use derive_builder::Builder;
+use std::error::Error;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ApiError {
+    BadRequest(String),
+}
+
+impl From<RequestBuilderError> for ApiError {
+    fn from(err: RequestBuilderError) -> Self {
+        ApiError::BadRequest(err.to_string())
+    }
+}
+
+#[derive(Builder)]
+#[builder(error = "ApiError")]
+pub struct Request {
+    path: String,
+}
+
+fn main() {
+    let err = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(err, CommandBuilderError::UninitializedField("args")));
+    assert_eq!(err.to_string(), "args is not set.");
+
+    let err: Box<dyn Error> = Box::new(err);
+    assert_eq!(err.to_string(), "args is not set.");
+
+    let err = CommandBuilderError::from("executable is empty".to_owned());
+    assert!(matches!(err, CommandBuilderError::Validation(ref msg) if msg == "executable is empty"));
+    assert_eq!(err.to_string(), "executable is empty");
+
+    let err = Request::builder().build().err();
+    assert_eq!(err, Some(ApiError::BadRequest("path is not set.".to_owned())));
+}
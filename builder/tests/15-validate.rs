@@ -0,0 +1,61 @@
+// #[builder(validate = "...")] on the struct runs a check on the builder at
+// the start of `build()` and on a field runs a check on the final value of the
+// field. A failed check is returned from `build()` as a Validation error.
+//
+// A typestate builder returns a Result from `build()` once it has something to
+// validate.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(validate = "RangeBuilder::check")]
+pub struct Range {
+    min: u32,
+    #[builder(validate = "not_zero")]
+    max: u32,
+    #[builder(default = "max - min", validate = "not_zero")]
+    len: u32,
+}
+
+impl RangeBuilder {
+    fn check(&self) -> Result<(), String> {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) if min > max => Err(format!("min {} > max {}", min, max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn not_zero(value: &u32) -> Result<(), String> {
+    match value {
+        0 => Err("cannot be 0".to_owned()),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Port {
+    #[builder(validate = "not_zero")]
+    port: u32,
+}
+
+fn main() {
+    let range = Range::builder().min(1).max(4).build().unwrap();
+    assert_eq!(range.len, 3);
+
+    let err = Range::builder().min(5).max(4).build().err().unwrap();
+    assert!(matches!(err, RangeBuilderError::Validation(ref msg) if msg == "min 5 > max 4"));
+
+    let err = Range::builder().min(0).max(0).build().err().unwrap();
+    assert!(matches!(err, RangeBuilderError::Validation(ref msg) if msg == "cannot be 0"));
+
+    let err = Range::builder().min(1).max(1).build().err().unwrap();
+    assert!(matches!(err, RangeBuilderError::Validation(ref msg) if msg == "cannot be 0"));
+
+    let err = Range::builder().min(1).build().err().unwrap();
+    assert!(matches!(err, RangeBuilderError::UninitializedField("max")));
+
+    assert_eq!(Port::builder().port(80).build().unwrap().port, 80);
+    assert!(Port::builder().port(0).build().is_err());
+}
@@ -0,0 +1,62 @@
+// With #[builder(pattern = "owned")] the setters take and return the builder
+// by value and `build()` moves the fields out of it, so field types that are
+// not Clone can be built.
+//
+// With #[builder(pattern = "immutable")] the setters take `&self` and return a
+// new builder, leaving the one they were called on untouched.
+
+use derive_builder::Builder;
+use std::sync::mpsc::{channel, Receiver};
+
+pub struct Handle(u32);
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Worker {
+    handle: Handle,
+    jobs: Receiver<String>,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    name: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let (sender, jobs) = channel();
+    let worker = Worker::builder()
+        .handle(Handle(7))
+        .jobs(jobs)
+        .tag("io".to_owned())
+        .name("worker-7".to_owned())
+        .build()
+        .unwrap();
+
+    sender.send("job".to_owned()).unwrap();
+    assert_eq!(worker.handle.0, 7);
+    assert_eq!(worker.jobs.recv().unwrap(), "job");
+    assert_eq!(worker.tags, vec!["io"]);
+    assert_eq!(worker.name.as_deref(), Some("worker-7"));
+
+    let base = Command::builder().executable("cargo".to_owned());
+    let build = base.arg("build".to_owned());
+    let test = base.arg("test".to_owned()).current_dir("..".to_owned());
+
+    let command = base.build().unwrap();
+    assert!(command.args.is_empty());
+    assert!(command.current_dir.is_none());
+
+    let command = build.build().unwrap();
+    assert_eq!(command.args, vec!["build"]);
+
+    let command = test.build().unwrap();
+    assert_eq!(command.args, vec!["test"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+}
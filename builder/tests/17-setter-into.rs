@@ -0,0 +1,85 @@
+// With #[builder(setter(into))] the setters of a field are generic over
+// anything that is `Into` the type they take. On the struct it applies to all
+// fields and a field can opt back out with `setter(into = false)`.
+//
+// The setter of an Option field takes the inner type unless the field or the
+// struct has #[builder(setter(strip_option = false))]. Setting such a field to
+// `None` is not the same as leaving it unset, so it wins over a default and
+// over the builder it is merged into.
+
+use derive_builder::Builder;
+use std::path::PathBuf;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(into))]
+    executable: String,
+    #[builder(each = "arg", setter(into))]
+    args: Vec<String>,
+    #[builder(setter(into))]
+    current_dir: Option<PathBuf>,
+    #[builder(setter(strip_option = false))]
+    timeout: Option<u64>,
+}
+
+#[derive(Builder)]
+#[builder(typestate, setter(into, strip_option = false))]
+pub struct Request {
+    path: String,
+    #[builder(setter(into = false))]
+    body: Vec<u8>,
+    #[builder(setter(strip_option))]
+    method: Option<String>,
+    query: Option<String>,
+}
+
+#[derive(Builder)]
+pub struct Cmd {
+    #[builder(setter(strip_option = false), default = "Some(\"/\".to_owned())")]
+    dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .args(vec!["build".to_owned()])
+        .arg("--release")
+        .current_dir("/tmp")
+        .timeout(None)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir, Some(PathBuf::from("/tmp")));
+    assert_eq!(command.timeout, None);
+
+    let command = Command::builder()
+        .executable(String::from("rustc"))
+        .timeout(Some(30))
+        .build()
+        .unwrap();
+
+    assert_eq!(command.timeout, Some(30));
+
+    let request = Request::builder()
+        .path("/")
+        .body(b"{}".to_vec())
+        .method("POST")
+        .query(Some("a=1".to_owned()))
+        .build();
+
+    assert_eq!(request.path, "/");
+    assert_eq!(request.method.as_deref(), Some("POST"));
+    assert_eq!(request.query.as_deref(), Some("a=1"));
+
+    assert_eq!(Cmd::builder().build().unwrap().dir.as_deref(), Some("/"));
+    assert_eq!(Cmd::builder().dir(None).build().unwrap().dir, None);
+
+    let mut cmd = Cmd::builder();
+    cmd.dir(Some("/tmp".to_owned()));
+    assert_eq!(cmd.get_dir(), Some(&Some("/tmp".to_owned())));
+    let mut unset = Cmd::builder();
+    unset.dir(None);
+    assert_eq!(cmd.merge(unset).build().unwrap().dir, None);
+}
@@ -0,0 +1,60 @@
+// The #[builder(each = "...")] setters work for any collection that is Default
+// and Extend. Maps get a setter that takes a key and a value, other
+// collections one that takes an item, and every `each` field gets an
+// `extend_<field>` method that takes any IntoIterator of its items.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+#[derive(Builder)]
+pub struct Request {
+    #[builder(each = "header")]
+    headers: HashMap<String, String>,
+    #[builder(each = "param", setter(into))]
+    params: BTreeMap<String, String>,
+    #[builder(each = "tag")]
+    tags: HashSet<String>,
+    #[builder(each = "retry")]
+    retries: VecDeque<u32>,
+    #[builder(each = "args")]
+    args: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Response {
+    status: u16,
+    #[builder(each = "header")]
+    headers: HashMap<String, String>,
+}
+
+fn main() {
+    let request = Request::builder()
+        .header("Accept".to_owned(), "*/*".to_owned())
+        .extend_headers(vec![("Host".to_owned(), "localhost".to_owned())])
+        .param("q", "rust")
+        .tag("a".to_owned())
+        .tag("a".to_owned())
+        .retry(1)
+        .extend_retries([2, 3])
+        .args("build".to_owned())
+        .extend_args(["--release".to_owned()])
+        .build()
+        .unwrap();
+
+    assert_eq!(request.headers.len(), 2);
+    assert_eq!(request.headers["Host"], "localhost");
+    assert_eq!(request.params["q"], "rust");
+    assert_eq!(request.tags.len(), 1);
+    assert_eq!(request.retries, [1, 2, 3]);
+    assert_eq!(request.args, vec!["build", "--release"]);
+
+    let response = Response::builder()
+        .header("Server".to_owned(), "workshop".to_owned())
+        .extend_headers(HashMap::new())
+        .status(200)
+        .build();
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.headers["Server"], "workshop");
+}
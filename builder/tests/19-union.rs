@@ -0,0 +1,12 @@
+// The builder can only be derived for structs and enums, a union gets a
+// spanned error instead of a panic from inside the macro.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub union Value {
+    int: u64,
+    float: f64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+// A tuple struct has no field names to name the setters after, so the macro
+// reports an error on its fields.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command(String, Vec<String>);
+
+fn main() {}
@@ -0,0 +1,19 @@
+// #[builder(each = "...")] needs a collection type to know the type of the
+// items it adds, like the `T` of a `Vec<T>` or the `K` and `V` of a
+// `HashMap<K, V>`. A type alias hides those from the macro.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+type Env = HashMap<String, String>;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: String,
+    #[builder(each = "env")]
+    env: Env,
+}
+
+fn main() {}
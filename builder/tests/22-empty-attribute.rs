@@ -0,0 +1,13 @@
+// An empty #[builder()] attribute on a field is reported the same way as one
+// with an unrecognized argument.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder()]
+    args: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+// The macro collects every error it finds and reports them all at once, so a
+// struct with three broken fields gets three errors instead of just the first.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "shared")]
+pub struct Command {
+    #[builder(eac = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: String,
+    #[builder(default = "1 +")]
+    retries: u32,
+}
+
+fn main() {}
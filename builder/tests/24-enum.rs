@@ -0,0 +1,86 @@
+// Deriving the builder for an enum gives every variant its own builder, named
+// `{Enum}{Variant}Builder` and returned from `Enum::{variant}_builder()`, with
+// the same setters and `build()` a struct with those fields gets. An acronym
+// in the name of a variant is kept as one word, like `http_response_builder()`
+// for `HTTPResponse`. The builder of a variant keeps all the generic params of
+// the enum, even the ones it doesn't use.
+
+use derive_builder::Builder;
+
+#[derive(Debug, PartialEq, Builder)]
+pub enum Msg {
+    Connect {
+        host: String,
+        #[builder(default = "80")]
+        port: u16,
+        #[builder(each = "protocol")]
+        protocols: Vec<String>,
+        timeout: Option<u64>,
+    },
+    Ping,
+    HttpRequest {
+        path: String,
+    },
+    HTTPResponse {
+        status: u16,
+    },
+    IOError,
+}
+
+#[derive(Builder)]
+#[builder(typestate, setter(into))]
+pub enum Shape<T> {
+    Circle { radius: T },
+    Rect { width: T, height: T },
+    Point,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+pub enum Node<'a, T> {
+    Leaf { value: &'a T },
+    Empty,
+}
+
+fn main() {
+    let msg = Msg::connect_builder()
+        .host("localhost".to_owned())
+        .protocol("h2".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        msg,
+        Msg::Connect {
+            host: "localhost".to_owned(),
+            port: 80,
+            protocols: vec!["h2".to_owned()],
+            timeout: None,
+        }
+    );
+
+    assert_eq!(Msg::ping_builder().build().unwrap(), Msg::Ping);
+
+    let err = Msg::http_request_builder().build().err().unwrap();
+    assert!(matches!(err, MsgHttpRequestBuilderError::UninitializedField("path")));
+
+    let mut builder: MsgHttpRequestBuilder = Msg::http_request_builder();
+    builder.path("/".to_owned());
+    assert_eq!(builder.build().unwrap(), Msg::HttpRequest { path: "/".to_owned() });
+
+    let msg = Msg::http_response_builder().status(200).build().unwrap();
+    assert_eq!(msg, Msg::HTTPResponse { status: 200 });
+    assert_eq!(Msg::io_error_builder().build().unwrap(), Msg::IOError);
+
+    match Shape::<u32>::rect_builder().width(2u8).height(3u8).build() {
+        Shape::Rect { width, height } => assert_eq!(width * height, 6),
+        Shape::Circle { .. } | Shape::Point => unreachable!(),
+    }
+
+    let circle: Shape<f64> = Shape::circle_builder().radius(1.5f32).build();
+    assert!(matches!(circle, Shape::Circle { radius } if radius == 1.5));
+    assert!(matches!(Shape::<f64>::point_builder().build(), Shape::Point));
+
+    let value = 1;
+    assert_eq!(Node::leaf_builder().value(&value).build().unwrap(), Node::Leaf { value: &1 });
+    assert_eq!(Node::<u8>::empty_builder().build().unwrap(), Node::Empty);
+}
@@ -0,0 +1,16 @@
+// The variants of an enum need named fields just like a struct, the builder
+// options go on the enum itself, and there is no `Default` impl to take the
+// unset fields of a variant from.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(default)]
+pub enum Msg {
+    Connect { host: String },
+    Data(Vec<u8>),
+    #[builder(typestate)]
+    Ping,
+}
+
+fn main() {}
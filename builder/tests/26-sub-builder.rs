@@ -0,0 +1,91 @@
+// A field whose type derives `Builder` can be built in place with
+// `builder(sub_builder)`. The parent builder stores the child's builder, hands it
+// out from a `&mut self` method named after the field and builds it in its own
+// `build()`, putting the child's errors under the name of the field.
+//
+// The child can live in another module when its builder is public, and its
+// builder and `build()` can be renamed with `sub_builder(name = "...",
+// build_fn = "...")`.
+
+use derive_builder::Builder;
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Config {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+    #[builder(sub_builder = "ServerBuilder")]
+    fallback: Server,
+}
+
+mod net {
+    use derive_builder::Builder;
+
+    #[derive(Debug, PartialEq, Builder)]
+    #[builder(name = "ProxyOptions", vis = "pub", setter_vis = "pub", build_fn(name = "finish"))]
+    pub struct Proxy {
+        pub url: String,
+    }
+}
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Client {
+    #[builder(sub_builder(name = "net::ProxyOptions", build_fn = "finish"))]
+    proxy: net::Proxy,
+}
+
+fn main() {
+    let mut builder = Config::builder();
+    builder.name("app".to_owned());
+    builder.server().host("localhost".to_owned()).port(80);
+    builder.server().tls().cert("server.pem".to_owned());
+
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "fallback.host is not set.");
+
+    builder.fallback().host("backup".to_owned()).port(8080);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "fallback.tls.cert is not set.");
+
+    builder.fallback().tls().cert("backup.pem".to_owned());
+    let config = builder.build().unwrap();
+    assert_eq!(
+        config,
+        Config {
+            name: "app".to_owned(),
+            server: Server {
+                host: "localhost".to_owned(),
+                port: 80,
+                tls: Tls { cert: "server.pem".to_owned() },
+            },
+            fallback: Server {
+                host: "backup".to_owned(),
+                port: 8080,
+                tls: Tls { cert: "backup.pem".to_owned() },
+            },
+        }
+    );
+
+    let mut builder = Client::builder();
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "proxy.url is not set.");
+
+    builder.proxy().url("http://proxy".to_owned());
+    let mut other = Client::builder();
+    other.proxy().url("http://other".to_owned());
+    let client = builder.merge(other).build().unwrap();
+    assert_eq!(client.proxy.url, "http://other");
+}
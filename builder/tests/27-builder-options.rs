@@ -0,0 +1,55 @@
+// The options on the struct control the name of the builder, the visibility of
+// the builder, `builder()` and the setters, the traits derived on the builder
+// and the name of `build()`, so the builder can be used outside of the module
+// that defines the struct.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Debug, PartialEq, Builder)]
+    #[builder(name = "ConfigOptions", vis = "pub", setter_vis = "pub")]
+    #[builder(derive(Debug, Clone, PartialEq), build_fn(name = "finish"))]
+    pub struct Config {
+        pub name: String,
+        #[builder(each = "tag")]
+        pub tags: Vec<String>,
+        pub port: Option<u16>,
+    }
+
+    #[derive(Builder)]
+    #[builder(typestate, vis = "pub(crate)", setter_vis = "pub(crate)", derive(Debug))]
+    pub struct Token {
+        pub value: String,
+    }
+}
+
+use config::{Config, ConfigOptions, ConfigOptionsError, Token};
+
+fn main() {
+    let mut options: ConfigOptions = Config::builder();
+    options.name("app".to_owned()).tag("web".to_owned());
+
+    let copy = options.clone();
+    assert_eq!(copy, options);
+    let _ = format!("{:?}", copy);
+
+    options.port(80);
+    assert_ne!(copy, options);
+
+    let config = options.finish().unwrap();
+    assert_eq!(
+        config,
+        Config {
+            name: "app".to_owned(),
+            tags: vec!["web".to_owned()],
+            port: Some(80),
+        }
+    );
+
+    let err: ConfigOptionsError = ConfigOptions::finish(&Config::builder()).unwrap_err();
+    assert_eq!(err.to_string(), "name is not set.");
+
+    let token = Token::builder().value("secret".to_owned());
+    let _ = format!("{:?}", token);
+    assert_eq!(token.build().value, "secret");
+}
@@ -0,0 +1,75 @@
+// Fields with `builder(skip)` are left out of the builder and fields with
+// `builder(setter(skip))` get no setter. `build()` fills both from their own
+// default, the default of the struct, or `Default` when there is neither.
+// A skipped field can be the only one that uses a generic param.
+
+use derive_builder::Builder;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+#[derive(Builder)]
+pub struct Client {
+    url: String,
+    #[builder(skip)]
+    cache: Arc<Mutex<Vec<String>>>,
+    #[builder(skip, default = "String::from(\"client\")")]
+    name: String,
+    #[builder(setter(skip), default = "3")]
+    retries: u32,
+    #[builder(setter(skip))]
+    timeout: Option<u64>,
+}
+
+#[derive(Default, Builder)]
+#[builder(default)]
+pub struct Limits {
+    max: u32,
+    #[builder(skip)]
+    used: u32,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Session {
+    user: String,
+    #[builder(skip)]
+    requests: u64,
+}
+
+#[derive(Builder)]
+pub struct Id<T> {
+    value: u64,
+    #[builder(skip)]
+    marker: PhantomData<T>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Handle<'a, T> {
+    name: String,
+    #[builder(skip)]
+    marker: PhantomData<&'a T>,
+}
+
+fn main() {
+    let client = Client::builder().url("localhost".to_owned()).build().unwrap();
+    assert_eq!(client.url, "localhost");
+    assert!(client.cache.lock().unwrap().is_empty());
+    assert_eq!(client.name, "client");
+    assert_eq!(client.retries, 3);
+    assert_eq!(client.timeout, None);
+
+    let limits = Limits::builder().max(5).build().unwrap();
+    assert_eq!(limits.max, 5);
+    assert_eq!(limits.used, 0);
+
+    let session = Session::builder().user("root".to_owned()).build();
+    assert_eq!(session.user, "root");
+    assert_eq!(session.requests, 0);
+
+    let id: Id<String> = Id::builder().value(7).build().unwrap();
+    assert_eq!(id.value, 7);
+
+    let handle: Handle<u8> = Handle::builder().name("h".to_owned()).build();
+    assert_eq!(handle.name, "h");
+}
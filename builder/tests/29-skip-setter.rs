@@ -0,0 +1,17 @@
+// A field with `builder(setter(skip))` has no setter on the builder.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Client {
+    url: String,
+    #[builder(setter(skip), default = "3")]
+    retries: u32,
+}
+
+fn main() {
+    let _ = Client::builder()
+        .url("localhost".to_owned())
+        .retries(5)
+        .build();
+}
@@ -0,0 +1,100 @@
+// The builder of a struct is `From` the struct with every field set from it, so
+// an existing value can be tweaked and built again, and the builder has a
+// `get_<field>()` for every field it stores to read them back.
+//
+// Taking the fields out of a struct that implements `Drop` doesn't run its
+// `Drop`, the same as destructuring it, and drops the skipped fields.
+
+use derive_builder::Builder;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Clone, Debug, PartialEq, Builder)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Clone, Debug, PartialEq, Builder)]
+pub struct Config {
+    name: String,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    timeout: Option<u64>,
+    #[builder(sub_builder)]
+    server: Server,
+    #[builder(skip)]
+    cache: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(typestate)]
+pub struct Token {
+    value: String,
+    expires: Option<u64>,
+}
+
+static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Builder)]
+pub struct Lock {
+    path: String,
+    #[builder(skip)]
+    held: Rc<()>,
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        DROPS.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn main() {
+    let base = Config {
+        name: "app".to_owned(),
+        tags: vec!["web".to_owned()],
+        timeout: None,
+        server: Server {
+            host: "localhost".to_owned(),
+            port: 80,
+        },
+        cache: vec![1, 2, 3],
+    };
+
+    let mut builder = ConfigBuilder::from(base.clone());
+    assert_eq!(builder.get_name(), Some(&"app".to_owned()));
+    assert_eq!(builder.get_tags(), &vec!["web".to_owned()]);
+    assert_eq!(builder.get_timeout(), None);
+    assert_eq!(builder.get_server().get_port(), Some(&80));
+
+    builder.timeout(30).tag("api".to_owned());
+    builder.server().port(8080);
+    assert_eq!(builder.get_timeout(), Some(&30));
+
+    let config = builder.build().unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(config.tags, vec!["web".to_owned(), "api".to_owned()]);
+    assert_eq!(config.timeout, Some(30));
+    assert_eq!(config.server.port, 8080);
+    assert!(config.cache.is_empty());
+
+    let builder = Config::builder();
+    assert_eq!(builder.get_name(), None);
+
+    let token = Token::builder().value("secret".to_owned()).build();
+    let token = TokenBuilder::from(token).expires(60).build();
+    assert_eq!(
+        token,
+        Token {
+            value: "secret".to_owned(),
+            expires: Some(60),
+        }
+    );
+
+    let held = Rc::new(());
+    let lock = Lock { path: "/tmp/lock".to_owned(), held: Rc::clone(&held) };
+    let builder = LockBuilder::from(lock);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+    assert_eq!(Rc::strong_count(&held), 1);
+    assert_eq!(builder.get_path(), Some(&"/tmp/lock".to_owned()));
+}
@@ -0,0 +1,77 @@
+// With the `serde` feature `builder(derive(Deserialize))` makes the builder
+// deserializable with the fields missing from the input left unset, so a
+// partial config can be loaded, changed with the setters, and `build()` says
+// which fields are still missing. Fields without a setter can't be set from
+// the input either.
+
+use derive_builder::Builder;
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(derive(Debug, Deserialize))]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(derive(Debug, serde::Deserialize))]
+pub struct Config {
+    name: String,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    timeout: Option<u64>,
+    #[builder(sub_builder)]
+    server: Server,
+    #[builder(skip)]
+    cache: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(derive(Deserialize))]
+pub struct Client {
+    url: String,
+    #[builder(setter(skip), default = "3")]
+    retries: u32,
+    #[builder(setter(custom), default)]
+    token: String,
+}
+
+fn main() {
+    let input = r#"{
+        "name": "app",
+        "tags": ["web"],
+        "server": { "host": "localhost" }
+    }"#;
+    let mut builder: ConfigBuilder = serde_json::from_str(input).unwrap();
+
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "server.port is not set.");
+
+    builder.server().port(80);
+    builder.tag("api".to_owned()).timeout(30);
+    let config = builder.build().unwrap();
+    assert_eq!(
+        config,
+        Config {
+            name: "app".to_owned(),
+            tags: vec!["web".to_owned(), "api".to_owned()],
+            timeout: Some(30),
+            server: Server {
+                host: "localhost".to_owned(),
+                port: 80,
+            },
+            cache: vec![],
+        }
+    );
+
+    let builder: ConfigBuilder = serde_json::from_str("{}").unwrap();
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "name is not set.");
+
+    let input = r#"{ "url": "x", "retries": 99, "token": "secret" }"#;
+    let builder: ClientBuilder = serde_json::from_str(input).unwrap();
+    let client = builder.build().unwrap();
+    assert_eq!(client.retries, 3);
+    assert_eq!(client.token, "");
+}
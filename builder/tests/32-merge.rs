@@ -0,0 +1,81 @@
+// `merge()` combines another builder into this one. A set field of the other
+// builder replaces the value unless it has `builder(merge = "keep")`, `each`
+// collections are appended unless they have `builder(merge = "replace")`, and
+// sub-builders are merged in turn.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Config {
+    name: String,
+    #[builder(merge = "keep")]
+    owner: String,
+    timeout: Option<u64>,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    #[builder(each = "env", merge = "replace")]
+    envs: HashMap<String, String>,
+    #[builder(each = "feature", merge = "keep")]
+    features: Vec<String>,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(pattern = "owned")]
+pub struct Limits {
+    max: u32,
+    min: Option<u32>,
+}
+
+fn main() {
+    let mut defaults = Config::builder();
+    defaults
+        .name("default".to_owned())
+        .owner("root".to_owned())
+        .timeout(10)
+        .tag("base".to_owned())
+        .env("A".to_owned(), "1".to_owned())
+        .feature("tls".to_owned());
+    defaults.server().host("localhost".to_owned()).port(80);
+
+    let mut file = Config::builder();
+    file.name("app".to_owned())
+        .owner("admin".to_owned())
+        .tag("web".to_owned())
+        .env("B".to_owned(), "2".to_owned())
+        .feature("gzip".to_owned());
+    file.server().port(8080);
+
+    let cli = Config::builder();
+
+    let config = defaults.merge(file).merge(cli).build().unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(config.owner, "root");
+    assert_eq!(config.timeout, Some(10));
+    assert_eq!(config.tags, vec!["base".to_owned(), "web".to_owned()]);
+    assert_eq!(config.envs.len(), 1);
+    assert_eq!(config.envs["B"], "2");
+    assert_eq!(config.features, vec!["tls".to_owned()]);
+    assert_eq!(
+        config.server,
+        Server {
+            host: "localhost".to_owned(),
+            port: 8080,
+        }
+    );
+
+    let limits = Limits::builder()
+        .max(5)
+        .merge(Limits::builder().min(1))
+        .build()
+        .unwrap();
+    assert_eq!(limits, Limits { max: 5, min: Some(1) });
+}
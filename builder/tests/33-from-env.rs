@@ -0,0 +1,75 @@
+// `from_env()` starts a builder with the fields set from their environment
+// variables, named by `builder(env_prefix = "...")` and the field or by the
+// `builder(env = "...")` of the field. The values are parsed with `FromStr`,
+// `each` collections are split on their separator and a value that can't be
+// parsed is an `Env` error naming the variable. A raw identifier is read
+// without its `r#` and `builder(env = false)` leaves a field out.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(env_prefix = "TEST_APP_")]
+pub struct Config {
+    name: String,
+    #[builder(env = "TEST_PORT")]
+    port: u16,
+    timeout: Option<u64>,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+    #[builder(each = "limit", env_separator = ";")]
+    limits: HashMap<String, u32>,
+    #[builder(setter(skip))]
+    secret: String,
+    r#type: String,
+    #[builder(env = false, default)]
+    raw: Vec<u8>,
+    #[builder(setter(custom), default)]
+    token: String,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Server {
+    host: String,
+    #[builder(env = "TEST_SERVER_PORT")]
+    port: u16,
+}
+
+fn main() {
+    std::env::set_var("TEST_APP_NAME", "app");
+    std::env::set_var("TEST_PORT", "80");
+    std::env::set_var("TEST_APP_TAGS", "web, api");
+    std::env::set_var("TEST_APP_LIMITS", "cpu=2;mem=512");
+    std::env::set_var("TEST_APP_SECRET", "ignored");
+    std::env::set_var("TEST_APP_TYPE", "json");
+    std::env::set_var("TEST_APP_TOKEN", "ignored");
+
+    let mut builder = ConfigBuilder::from_env().unwrap();
+    builder.tag("cli".to_owned());
+    let config = builder.build().unwrap();
+    assert_eq!(config.name, "app");
+    assert_eq!(config.port, 80);
+    assert_eq!(config.timeout, None);
+    assert_eq!(config.tags, vec!["web", "api", "cli"]);
+    assert_eq!(config.limits["cpu"], 2);
+    assert_eq!(config.limits["mem"], 512);
+    assert_eq!(config.secret, "");
+    assert_eq!(config.r#type, "json");
+    assert!(config.raw.is_empty());
+    assert_eq!(config.token, "");
+
+    std::env::set_var("TEST_APP_TIMEOUT", "soon");
+    let err = ConfigBuilder::from_env().err().unwrap();
+    assert_eq!(err.to_string(), "TEST_APP_TIMEOUT is invalid: invalid digit found in string");
+    std::env::remove_var("TEST_APP_TIMEOUT");
+
+    std::env::set_var("TEST_APP_LIMITS", "cpu");
+    let err = ConfigBuilder::from_env().err().unwrap();
+    assert_eq!(err.to_string(), "TEST_APP_LIMITS is invalid: expected `key=value`");
+
+    std::env::set_var("TEST_SERVER_PORT", "8080");
+    let mut builder = ServerBuilder::from_env().unwrap();
+    assert_eq!(builder.get_host(), None);
+    let server = builder.host("localhost".to_owned()).build().unwrap();
+    assert_eq!(server.port, 8080);
+}
@@ -0,0 +1,72 @@
+// With `builder(no_std)` the generated code only uses paths into `core` and
+// `alloc`, so it works in a `#![no_std]` crate that has `extern crate alloc`.
+// The `std` module below takes the place of the standard library so that any
+// path into it fails to compile.
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use derive_builder::Builder;
+
+#[allow(dead_code)]
+mod std {}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(no_std)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(default = "3", validate = "check_retries")]
+    retries: u32,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(no_std)]
+pub struct Limits {
+    max: u32,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(no_std, typestate)]
+pub struct Token {
+    value: u64,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(no_std, pattern = "immutable")]
+pub enum Msg {
+    Ping { id: u32 },
+}
+
+fn check_retries(retries: &u32) -> Result<(), String> {
+    if *retries < 10 {
+        Ok(())
+    } else {
+        Err(String::from("too many retries"))
+    }
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder.executable(String::from("cargo")).arg(String::from("build"));
+    builder.merge(Command::builder());
+
+    let err = builder.build().unwrap_err();
+    assert_eq!(alloc::format!("{}", err), "limits.max is not set.");
+
+    builder.limits().max(5);
+    let command = builder.build().unwrap();
+    assert_eq!(command.args, alloc::vec![String::from("build")]);
+    assert_eq!(command.retries, 3);
+
+    let msg = Msg::ping_builder().id(1).build().unwrap();
+    assert_eq!(msg, Msg::Ping { id: 1 });
+
+    let token = Token::builder().value(1).build();
+    assert_eq!(token.value, 1);
+}
@@ -0,0 +1,58 @@
+// With `builder(const)` the builder, its setters and `build()` are `const fn`s
+// so a builder can make the value of a `const` or `static`, and `build()`
+// panics with the name of a field that is not set. Generic fields have to be
+// `Copy` to be dropped in a `const fn`.
+
+use derive_builder::Builder;
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(const)]
+pub struct Server {
+    host: &'static str,
+    port: u16,
+    timeout: Option<u64>,
+    #[builder(default = "4")]
+    workers: u8,
+    #[builder(skip, default = "\"v1\"")]
+    version: &'static str,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(const)]
+pub struct Pair<T: Copy> {
+    first: T,
+    second: T,
+}
+
+static SERVER: Server = Server::builder().host("localhost").port(80).build();
+
+const PAIR: Pair<u8> = Pair::builder().first(1).second(2).build();
+
+const fn server() -> Server {
+    Server::builder().host("0.0.0.0").port(8080).timeout(30).workers(8).build()
+}
+
+fn main() {
+    assert_eq!(
+        SERVER,
+        Server {
+            host: "localhost",
+            port: 80,
+            timeout: None,
+            workers: 4,
+            version: "v1",
+        }
+    );
+    assert_eq!(PAIR, Pair { first: 1, second: 2 });
+
+    const SERVER_8080: Server = server();
+    assert_eq!(SERVER_8080.timeout, Some(30));
+    assert_eq!(SERVER_8080.workers, 8);
+
+    let builder = Server::builder().host("localhost");
+    assert_eq!(builder.get_port(), None);
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = std::panic::catch_unwind(|| builder.build());
+    let err = result.unwrap_err();
+    assert_eq!(err.downcast_ref::<&str>(), Some(&"port is not set."));
+}
@@ -0,0 +1,17 @@
+// A const builder that is missing a field in a `const` is a compile error, with
+// the name of the field in the panic message.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Server {
+    host: &'static str,
+    port: u16,
+}
+
+const SERVER: Server = Server::builder().host("localhost").build();
+
+fn main() {
+    let _ = SERVER.port;
+}
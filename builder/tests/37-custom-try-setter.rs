@@ -0,0 +1,66 @@
+// A field with `builder(setter(custom))` is stored and built as usual but gets
+// no setter, so one can be written by hand. `builder(try_setter)` adds a
+// `try_<name>()` that converts its argument with `TryInto` before setting it and
+// returns the error of the conversion if that fails.
+
+use derive_builder::Builder;
+use std::num::TryFromIntError;
+
+#[derive(Debug, PartialEq, Builder)]
+pub struct Server {
+    #[builder(setter(custom))]
+    host: String,
+    #[builder(try_setter)]
+    port: u16,
+    #[builder(try_setter)]
+    timeout: Option<u32>,
+}
+
+impl ServerBuilder {
+    fn host(&mut self, host: &str) -> &mut Self {
+        self.host = Some(host.to_lowercase());
+        self
+    }
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(pattern = "owned", try_setter)]
+pub struct Limits {
+    max: u8,
+    #[builder(setter(into))]
+    name: String,
+}
+
+#[derive(Debug, PartialEq, Builder)]
+#[builder(typestate, try_setter)]
+pub struct Token {
+    id: u8,
+    expires: Option<u8>,
+}
+
+fn main() -> Result<(), TryFromIntError> {
+    let mut builder = Server::builder();
+    builder.host("LOCALHOST");
+    assert!(builder.try_port(-1).is_err());
+    assert!(builder.try_port(70_000u32).is_err());
+    builder.try_port(80u64)?.try_timeout(30i64)?;
+    let server = builder.build().unwrap();
+    assert_eq!(
+        server,
+        Server {
+            host: "localhost".to_owned(),
+            port: 80,
+            timeout: Some(30),
+        }
+    );
+
+    let limits = Limits::builder().try_max(200)?.try_name("cpu")?.build().unwrap();
+    assert_eq!(limits, Limits { max: 200, name: "cpu".to_owned() });
+    assert!(Limits::builder().try_max(300).is_err());
+
+    let token = Token::builder().try_expires(10)?.try_id(1)?.build();
+    assert_eq!(token, Token { id: 1, expires: Some(10) });
+    assert!(Token::builder().try_id(256).is_err());
+
+    Ok(())
+}
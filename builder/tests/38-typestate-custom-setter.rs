@@ -0,0 +1,18 @@
+// A hand-written setter can't move a typestate builder into the `Set` state
+// of a required field, so `setter(custom)` is only allowed on the fields a
+// typestate builder doesn't require.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    #[builder(setter(custom))]
+    executable: String,
+    #[builder(setter(custom), default)]
+    args: Vec<String>,
+    #[builder(setter(custom))]
+    current_dir: Option<String>,
+}
+
+fn main() {}
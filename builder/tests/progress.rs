@@ -10,4 +10,34 @@ fn tests() {
     t.pass("tests/07-repeated-field.rs");
     t.compile_fail("tests/08-unrecognized-attribute.rs");
     t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-typestate.rs");
+    t.compile_fail("tests/11-typestate-missing-field.rs");
+    t.pass("tests/12-generics.rs");
+    t.pass("tests/13-default.rs");
+    t.pass("tests/14-build-error.rs");
+    t.pass("tests/15-validate.rs");
+    t.pass("tests/16-builder-pattern.rs");
+    t.pass("tests/17-setter-into.rs");
+    t.pass("tests/18-each-collections.rs");
+    t.compile_fail("tests/19-union.rs");
+    t.compile_fail("tests/20-tuple-struct.rs");
+    t.compile_fail("tests/21-each-not-collection.rs");
+    t.compile_fail("tests/22-empty-attribute.rs");
+    t.compile_fail("tests/23-multiple-errors.rs");
+    t.pass("tests/24-enum.rs");
+    t.compile_fail("tests/25-enum-errors.rs");
+    t.pass("tests/26-sub-builder.rs");
+    t.pass("tests/27-builder-options.rs");
+    t.pass("tests/28-skip.rs");
+    t.compile_fail("tests/29-skip-setter.rs");
+    t.pass("tests/30-from-value.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/31-serde.rs");
+    t.pass("tests/32-merge.rs");
+    t.pass("tests/33-from-env.rs");
+    t.pass("tests/34-no-std.rs");
+    t.pass("tests/35-const.rs");
+    t.compile_fail("tests/36-const-missing-field.rs");
+    t.pass("tests/37-custom-try-setter.rs");
+    t.compile_fail("tests/38-typestate-custom-setter.rs");
 }
@@ -1,83 +1,94 @@
 
 use proc_macro::TokenStream;
-use quote::quote;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
 use syn::parse_macro_input;
+use syn::punctuated::Punctuated;
+use syn::visit::{self, Visit};
 
 #[proc_macro_derive(CustomDebug, attributes(debug))]
 pub fn derive(input: TokenStream) -> TokenStream {
 
     // -=-=- Helper Functions -=-=- //
 
-    let has_attr_err = |attrs: &Vec<syn::Attribute>| {
-        let err = |t: &dyn quote::ToTokens| {
-            syn::Error::new_spanned(t, "expected `debug = \"...\"`").to_compile_error()
-        };
-
-        for attr in attrs {
-            if let syn::Meta::NameValue(ref name_value) = attr.meta {
-                // if name_value.path.segments.len() != 1 && name_value.path.segments[0].ident != "debug" {
-                //     return Some(err(&name_value.path));
-                // } // assert debug
-
-                // if name_value.eq_token != syn::token::Eq::default() {
-                //     return Some(err(&name_value.eq_token));
-                // } // assert =
-                // eprintln!("{:#?}", name_value.eq_token);
-
-                if let syn::Lit::Str(_) = 
-                if let syn::Expr::Lit(syn::ExprLit { ref lit, .. }) = name_value.value
-                { lit } else { return Some(err(&name_value.value)) }
-                { return None; } else { return Some(err(&name_value.value)) }
-            }
-            return Some(err(attr));
-        }
-        None
+    // Make a standardized attr error and pass the tokens for the span
+    let make_attr_error = |t: &dyn quote::ToTokens| {
+        syn::Error::new_spanned(t, "expected `debug = \"...\"` or `debug(bound = \"...\")`")
     };
-    let has_debug_attr = |attrs: &Vec<syn::Attribute>| {
-        for attr in attrs {
-            if let syn::Meta::NameValue(ref name_value) = attr.meta {
-                if let syn::Lit::Str(string) = 
-                if let syn::Expr::Lit(syn::ExprLit { ref lit, .. }) = name_value.value
-                { lit } else { return None }
-                { return Some(string.to_owned()); } else { return None; }
-            }
-            return None;
-        }
-        None
+
+    // parse the where-predicates of a `debug(bound = "...")`
+    let parse_bound = |meta: &syn::meta::ParseNestedMeta| {
+        let bound: syn::LitStr = meta.value()?.parse()?;
+        bound.parse_with(Punctuated::<syn::WherePredicate, syn::Token![,]>::parse_terminated)
     };
 
-    let _in_where_stmt = |
-        param: &syn::GenericParam,
-        where_clause: &std::option::Option<&syn::WhereClause>
-    | {
-        if where_clause.is_none() { return None; }
-        let where_clause = where_clause.unwrap();
-
-        for predicate in &where_clause.predicates {
-            match predicate {
-                syn::WherePredicate::Type(ty) => {
-                    if let syn::GenericParam::Type(ty_param) = param {
-                        if let syn::Type::Path(syn::TypePath{path, ..}) = &ty.bounded_ty {
-                            if let Some(syn::PathSegment{ident, ..}) = path.segments.iter().last() {
-                                if ident == &ty_param.ident {
-                                    return Some(predicate.to_owned());
-                                }
-                            }
-                        }
-                    }
+    // parse the `#[debug...]` attrs on a field
+    // returns the options of the field or the error for a malformed debug attr
+    let parse_field_attrs = |field: &syn::Field| {
+        let mut field_attrs = FieldAttrs::default();
+        for attr in &field.attrs {
+            if !attr.path().is_ident("debug") { continue; }
+            match attr.meta {
+                // debug = "..."
+                syn::Meta::NameValue(ref name_value) => match name_value.value {
+                    syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Str(ref fmt), .. }) => {
+                        field_attrs.format = Some(fmt.to_owned());
+                    },
+                    ref value => return Err(make_attr_error(value)),
                 },
-                syn::WherePredicate::Lifetime(li) => {
-                    if let syn::GenericParam::Lifetime(li_param) = param {
-                        if li.lifetime.ident == li_param.lifetime.ident {
-                            return Some(predicate.to_owned());
+                // debug(...)
+                syn::Meta::List(_) => attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("bound") {
+                        // debug(bound = "...")
+                        field_attrs.bound = Some(parse_bound(&meta)?);
+                    } else if meta.path.is_ident("skip") {
+                        // debug(skip)
+                        field_attrs.skip = true;
+                    } else if meta.path.is_ident("rename") {
+                        // debug(rename = "...")
+                        let rename: syn::LitStr = meta.value()?.parse()?;
+                        if field.ident.is_none() {
+                            return Err(syn::Error::new_spanned(rename, "expected a named field to rename"));
+                        }
+                        field_attrs.rename = Some(rename);
+                    } else if meta.path.is_ident("redact") {
+                        // debug(redact) or debug(redact(keep = ...))
+                        let mut keep = None;
+                        if meta.input.peek(syn::token::Paren) {
+                            meta.parse_nested_meta(|meta| {
+                                if !meta.path.is_ident("keep") { return Err(meta.error("expected `keep`")); }
+                                keep = Some(meta.value()?.parse::<syn::LitInt>()?);
+                                Ok(())
+                            })?;
                         }
+                        field_attrs.redact = Some(keep);
+                    } else {
+                        return Err(meta.error("expected `bound`, `skip`, `rename` or `redact`"));
                     }
-                }, 
-                predicate => panic!("PANICING! FOUND {predicate:?}"),
+                    Ok(())
+                })?,
+                syn::Meta::Path(_) => return Err(make_attr_error(attr)),
             }
+        }
+        syn::Result::Ok(field_attrs)
+    };
 
+    // parse the `#[debug(...)]` attrs on the struct
+    // returns the options of the struct or the error for a malformed debug attr
+    let parse_struct_attrs = |attrs: &Vec<syn::Attribute>| {
+        let mut struct_attrs = StructAttrs::default();
+        for attr in attrs {
+            if !attr.path().is_ident("debug") { continue; }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    struct_attrs.bound = Some(parse_bound(&meta)?);
+                } else {
+                    return Err(meta.error("expected `bound = \"...\"`"));
+                }
+                Ok(())
+            })?;
         }
-    None
+        syn::Result::Ok(struct_attrs)
     };
 
     // -=-=- THING -=-=- //
@@ -90,88 +101,256 @@ pub fn derive(input: TokenStream) -> TokenStream {
     let struct_name = &input.ident;
     // let builder = syn::Ident::new(&format!("{}Builder", name), name.span());
 
-    // get the structs fields to operate on.
-    let fields =
-    if let syn::Data::Struct(data) = &input.data {
-        &data.fields
-    } else { panic!() };
-    
+    // get the fields to operate on, with the path and name of the struct or each of the
+    // variants of an enum they belong to.
+    let variants: Vec<(TokenStream2, &syn::Ident, &syn::Fields)> = match &input.data {
+        syn::Data::Struct(data) => vec![(quote! { Self }, struct_name, &data.fields)],
+        syn::Data::Enum(data) => data.variants.iter().map(|variant| {
+            let variant_name = &variant.ident;
+            (quote! { Self::#variant_name }, variant_name, &variant.fields)
+        }).collect(),
+        syn::Data::Union(data) => {
+            return syn::Error::new_spanned(data.union_token, "CustomDebug can only be derived for structs and enums")
+                .to_compile_error().into();
+        },
+    };
+    let fields: Vec<&syn::Field> = variants.iter().flat_map(|(_, _, fields)| fields.iter()).collect();
+
     // -=-=- Err Check -=-=- //
 
-    for field in fields {
-        // if attr parsing has error => raise the error
-        if let Some(err) = has_attr_err(&field.attrs) {
-            return err.into();
-        }
+    // every error found is collected so they can all be reported at once
+    let mut errors: Vec<syn::Error> = Vec::new();
+
+    let struct_attrs = parse_struct_attrs(&input.attrs).unwrap_or_else(|err| {
+        errors.push(err);
+        StructAttrs::default()
+    });
+
+    // the options of the fields of each variant
+    let variant_attrs: Vec<Vec<FieldAttrs>> = variants.iter().map(|(_, _, fields)| {
+        fields.iter().map(|field| {
+            // if attr parsing has error => collect the error
+            parse_field_attrs(field).unwrap_or_else(|err| {
+                errors.push(err);
+                FieldAttrs::default()
+            })
+        }).collect()
+    }).collect();
+    let field_attrs: Vec<&FieldAttrs> = variant_attrs.iter().flatten().collect();
+
+    // report all the errors together
+    if let Some(err) = errors.into_iter().reduce(|mut err, next| { err.combine(next); err }) {
+        return err.to_compile_error().into();
     }
 
     // -=-=- Generate Output -=-=- //
 
-    let debug_fields = fields.iter().enumerate()
-    .map(|(i, field)| {
-        let name = &field.ident;
+    // one match arm per variant, or for the struct, binding each field to `__field_{i}`
+    // and writing them with the `Formatter`'s `debug_struct` or `debug_tuple` so the
+    // `{:#?}` and other flags work like with `derive(Debug)`
+    let debug_arms = variants.iter().zip(&variant_attrs).map(|((path, name, fields), attrs)| {
+        // a `debug(skip)` or plain `debug(redact)` field is not bound at all
+        let bindings: Vec<_> = attrs.iter().enumerate().map(|(i, attrs)| {
+            if attrs.skip || matches!(attrs.redact, Some(None)) {
+                quote! { _ }
+            } else {
+                let binding = format_ident!("__field_{}", i);
+                quote! { #binding }
+            }
+        }).collect();
 
-        let mut sep = None;
-        if i > 0 { sep = Some(quote! { write!(f, ", ")?; }) }
+        let debug_fields = fields.iter().zip(attrs).zip(&bindings).filter(|((_, attrs), _)| !attrs.skip)
+        .map(|((field, attrs), binding)| {
+            // a field with `#[debug = "..."]` is written through the `Arguments` of its
+            // format, which write the field the same way `write!` would
+            let value = match attrs.format {
+                Some(ref fmt) => quote! { std::format_args!(#fmt, #binding) },
+                None => quote! { #binding },
+            };
+            // a `debug(redact)` field is replaced by `<redacted>`, or by a mask with the last
+            // few chars of its format, or its `Display`, for `debug(redact(keep = ...))`
+            // where a value no longer than what is kept is masked completely
+            let value = match attrs.redact {
+                Some(None) => quote! { &std::format_args!("<redacted>") },
+                Some(Some(ref keep)) => {
+                    let fmt = attrs.format.as_ref().map_or(quote! { "{}" }, |fmt| quote! { #fmt });
+                    quote! {
+                        &std::format_args!("****{}", {
+                            let value = std::format!(#fmt, #binding);
+                            let len = value.chars().count();
+                            let skip = if len > #keep { len - #keep } else { len };
+                            value.chars().skip(skip).collect::<std::string::String>()
+                        })
+                    }
+                },
+                None => quote! { &#value },
+            };
+            // a tuple field has no name to write
+            match (&field.ident, &attrs.rename) {
+                (_, Some(rename)) => quote! { .field(#rename, #value) },
+                (Some(ident), None) => quote! { .field(stringify!(#ident), #value) },
+                (None, None) => quote! { .field(#value) },
+            }
+        });
+
+        // the skipped fields are left out with a `..`
+        let finish = if attrs.iter().any(|attrs| attrs.skip) {
+            quote! { finish_non_exhaustive }
+        } else {
+            quote! { finish }
+        };
 
-        let fmt = 
-        if let Some(fmt) = has_debug_attr(&field.attrs) { fmt }
-        else { syn::LitStr::new("{:?}", proc_macro2::Span::call_site()) };
-        
-        quote! { 
-            #sep
-            write!(f, concat!("{}: ", #fmt), stringify!(#name), &self.#name)?;
+        match fields {
+            syn::Fields::Named(_) if !fields.is_empty() => {
+                let idents = fields.iter().map(|field| &field.ident);
+                quote! {
+                    #path { #( #idents: #bindings ),* } => {
+                        f.debug_struct(stringify!(#name))
+                            #( #debug_fields )*
+                            .#finish()
+                    }
+                }
+            },
+            syn::Fields::Unnamed(_) => quote! {
+                #path( #( #bindings ),* ) => {
+                    f.debug_tuple(stringify!(#name))
+                        #( #debug_fields )*
+                        .#finish()
+                }
+            },
+            _ => quote! {
+                #path { .. } => f.write_str(stringify!(#name)),
+            },
         }
     });
 
-    let _struct_generic_types = &input.generics.params.iter().filter(|generic| {
-        match generic {
-            syn::GenericParam::Type(_) => true,
-            _ => false,
+    // -=-=- Bounds -=-=- //
+
+    // the `Debug` bounds the field types need, which are the type params they use
+    // outside of a `PhantomData` and the associated types of them they use, added to the
+    // where-clause of the struct.
+    // a `debug(bound = "...")` on the struct replaces all of them and one on a field
+    // replaces the ones of that field.
+    let type_params: Vec<_> = input.generics.type_params().map(|param| &param.ident).collect();
+    let mut bounds = BoundVisitor { type_params: &type_params, params: Vec::new(), assoc_types: Vec::new() };
+    let mut display_bounds = BoundVisitor { type_params: &type_params, params: Vec::new(), assoc_types: Vec::new() };
+    for (field, attrs) in fields.iter().zip(&field_attrs) {
+        if attrs.bound.is_some() || attrs.skip { continue; }
+        match attrs.redact {
+            // a redacted field isn't written with `Debug`, but the mask of a
+            // `debug(redact(keep = ...))` writes it with `Display` when it has no format
+            Some(Some(_)) if attrs.format.is_none() => display_bounds.visit_type(&field.ty),
+            Some(_) => {},
+            None => bounds.visit_type(&field.ty),
         }
-    });
-    let struct_where_stmt = if input.generics.params.is_empty() { None } else {
-        // let (_, _, where_clause) = input.generics.split_for_impl();
-        
-        // let predicates = input.generics.params.iter().filter_map(|param| {
-        //     if let Some(predicate) = in_where_stmt(param, &where_clause) {
-        //         match predicate {
-        //             syn::WherePredicate::Lifetime(li) => return Some(quote! { #li }),
-        //             syn::WherePredicate::Type(ty) => return Some(quote! {
-        //                 #ty + std::fmt::Debug
-        //             }),
-        //             _ => {},
-        //         }
-        //     };
-        //     if let syn::GenericParam::Type(ty) = param {
-        //         return Some(quote! {
-        //             #ty: std::fmt::Debug
-        //         });
-        //     }
-        //     None
-        // });
-
-        let predicates = fields.iter().map(|field| {
-            let ty = &field.ty;
-            quote! { #ty: std::fmt::Debug }
-        });
+    }
 
-        Some(quote! { where #(#predicates),* })
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    if let Some(ref bound) = struct_attrs.bound {
+        where_clause.predicates.extend(bound.iter().cloned());
+    } else {
+        for (bounds, fmt_trait) in [(&bounds, quote! { std::fmt::Debug }), (&display_bounds, quote! { std::fmt::Display })] {
+            let params = type_params.iter().filter(|param| bounds.params.contains(param));
+            for param in params {
+                where_clause.predicates.push(syn::parse_quote! { #param: #fmt_trait });
+            }
+            for ty in &bounds.assoc_types {
+                where_clause.predicates.push(syn::parse_quote! { #ty: #fmt_trait });
+            }
+        }
+    }
+    for bound in field_attrs.iter().filter_map(|attrs| attrs.bound.as_ref()) {
+        where_clause.predicates.extend(bound.iter().cloned());
+    }
+
+    // an enum without variants has no value to match on behind the reference
+    let debug_match = if variants.is_empty() {
+        quote! { match *self {} }
+    } else {
+        quote! { match self { #( #debug_arms )* } }
     };
 
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+    let (_, _, where_clause) = generics.split_for_impl();
     
     let output_tokens = quote! {
         impl #impl_generics std::fmt::Debug for #struct_name #ty_generics
-        #struct_where_stmt
+        #where_clause
 
         {
             fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-                write!(f, "{} {{ ", stringify!(#struct_name))?;
-                #( #debug_fields )*
-                write!(f, " }}")
+                #debug_match
             }
         }
     };
     output_tokens.into()
 }
+
+// -=-=- Attribute Types -=-=- //
+
+// the options parsed from the `#[debug(...)]` attrs on the struct
+#[derive(Default)]
+struct StructAttrs {
+    // `debug(bound = "...")`
+    bound: Option<Punctuated<syn::WherePredicate, syn::Token![,]>>,
+}
+
+// the options parsed from the `#[debug...]` attrs on a field
+#[derive(Default)]
+struct FieldAttrs {
+    // `debug = "..."`
+    format: Option<syn::LitStr>,
+    // `debug(bound = "...")`
+    bound: Option<Punctuated<syn::WherePredicate, syn::Token![,]>>,
+    // `debug(skip)`
+    skip: bool,
+    // `debug(rename = "...")`
+    rename: Option<syn::LitStr>,
+    // `debug(redact)` or `debug(redact(keep = ...))` with the number of chars to keep
+    redact: Option<Option<syn::LitInt>>,
+}
+
+// -=-=- Bound Inference -=-=- //
+
+// walks the field types for the type params and associated types that need a bound
+struct BoundVisitor<'a> {
+    // the type params of the struct
+    type_params: &'a [&'a syn::Ident],
+    // the type params used directly
+    params: Vec<&'a syn::Ident>,
+    // the associated types of the type params used, like `T::Value`
+    assoc_types: Vec<syn::TypePath>,
+}
+
+impl<'ast> Visit<'ast> for BoundVisitor<'_> {
+    fn visit_type_path(&mut self, ty: &'ast syn::TypePath) {
+        // `PhantomData<T>` is `Debug` for any `T`
+        if ty.path.segments.last().is_some_and(|segment| segment.ident == "PhantomData") {
+            return;
+        }
+
+        // the type param a path starts with, or the one of `<T as Trait>::Value`
+        let param = match ty.qself {
+            Some(ref qself) => match *qself.ty {
+                syn::Type::Path(ref qself) if qself.qself.is_none() && qself.path.segments.len() == 1 => {
+                    Some(&qself.path.segments[0].ident)
+                },
+                _ => None,
+            },
+            None if ty.path.leading_colon.is_none() => ty.path.segments.first().map(|segment| &segment.ident),
+            None => None,
+        };
+
+        if let Some(param) = param.and_then(|param| self.type_params.iter().find(|p| **p == param)) {
+            if ty.qself.is_none() && ty.path.segments.len() == 1 {
+                if !self.params.contains(param) { self.params.push(param); }
+            } else if !self.assoc_types.contains(ty) {
+                self.assoc_types.push(ty.to_owned());
+            }
+            return;
+        }
+
+        visit::visit_type_path(self, ty);
+    }
+}
@@ -0,0 +1,41 @@
+// CustomDebug works on tuple structs, unit structs and enums as well, writing
+// them the same way as the standard library's derive(Debug), with the
+// `#[debug = "..."]` format of a field still used for it.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Point(i32, #[debug = "0x{:02x}"] u8);
+
+#[derive(CustomDebug)]
+pub struct Unit;
+
+#[derive(CustomDebug)]
+pub struct Empty {}
+
+#[derive(CustomDebug)]
+pub enum Shape<T> {
+    Circle {
+        radius: T,
+        #[debug = "0b{:04b}"]
+        flags: u8,
+    },
+    Line(T, T),
+    Dot,
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn main() {
+    assert_eq!(format!("{:?}", Point(-1, 255)), "Point(-1, 0xff)");
+    assert_eq!(format!("{:?}", Unit), "Unit");
+    assert_eq!(format!("{:?}", Empty {}), "Empty");
+
+    let circle = Shape::Circle { radius: 1.5, flags: 5 };
+    assert_eq!(format!("{:?}", circle), "Circle { radius: 1.5, flags: 0b0101 }");
+    assert_eq!(format!("{:?}", Shape::Line("a", "b")), r#"Line("a", "b")"#);
+    assert_eq!(format!("{:?}", Shape::<u8>::Dot), "Dot");
+
+    let _ = |never: Never| format!("{:?}", never);
+}
@@ -0,0 +1,12 @@
+// CustomDebug can't be derived for a union, which is reported as an error on
+// the `union` keyword.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
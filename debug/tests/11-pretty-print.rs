@@ -0,0 +1,56 @@
+// The output is written with the `Formatter`'s `debug_struct` and `debug_tuple`,
+// so `{:#?}` pretty printing, padding and the other flags behave the same as
+// with the standard library's derive(Debug), including for nested values. A
+// `#[debug = "..."]` format is still used for its field.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Field {
+    name: &'static str,
+    #[debug = "0b{:08b}"]
+    bitmask: u8,
+    inner: Inner,
+}
+
+#[derive(CustomDebug)]
+pub struct Inner(Vec<u8>, Option<f32>);
+
+#[derive(Debug)]
+pub struct StdField {
+    name: &'static str,
+    bitmask: Bitmask,
+    inner: StdInner,
+}
+
+#[derive(Debug)]
+pub struct StdInner(Vec<u8>, Option<f32>);
+
+pub struct Bitmask(u8);
+
+impl std::fmt::Debug for Bitmask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0b{:08b}", self.0)
+    }
+}
+
+fn main() {
+    let field = Field {
+        name: "F",
+        bitmask: 0b00011100,
+        inner: Inner(vec![1, 2], Some(0.5)),
+    };
+    let std_field = StdField {
+        name: "F",
+        bitmask: Bitmask(0b00011100),
+        inner: StdInner(vec![1, 2], Some(0.5)),
+    };
+
+    assert_eq!(format!("{:?}", field), format!("{:?}", std_field).replace("Std", ""));
+    assert_eq!(format!("{:#?}", field), format!("{:#?}", std_field).replace("Std", ""));
+    assert_eq!(
+        format!("{:#?}", field.inner),
+        "Inner(\n    [\n        1,\n        2,\n    ],\n    Some(\n        0.5,\n    ),\n)",
+    );
+    assert_eq!(format!("{:.2?}", field.inner), "Inner([1, 2], Some(0.50))");
+}
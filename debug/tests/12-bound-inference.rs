@@ -0,0 +1,59 @@
+// The `Debug` bounds are inferred from how the type params are used by the
+// fields: a param used directly or inside another type is bounded, one only
+// used in a `PhantomData` is not, the associated types of a param are bounded
+// instead of the param, and the bounds are added to the where-clause of the
+// struct rather than replacing it.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Mixed<T, U, V: Trait, W>
+where
+    T: Clone,
+{
+    nested: Option<Vec<T>>,
+    marker: PhantomData<U>,
+    value: <V as Trait>::Value,
+    values: Vec<V::Value>,
+    both: (W, PhantomData<W>),
+}
+
+#[derive(CustomDebug)]
+pub enum Either<L, R> {
+    Left(L),
+    Right { value: R },
+    Neither(PhantomData<(L, R)>),
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    #[derive(Clone, Debug)]
+    struct Cloned;
+
+    struct NotDebug;
+
+    impl Trait for NotDebug {
+        type Value = u8;
+    }
+
+    assert_debug::<Mixed<Cloned, NotDebug, NotDebug, u8>>();
+    assert_debug::<Either<u8, u8>>();
+
+    let mixed: Mixed<u8, NotDebug, NotDebug, u8> = Mixed {
+        nested: Some(vec![1]),
+        marker: PhantomData,
+        value: 2,
+        values: vec![3],
+        both: (4, PhantomData),
+    };
+    let debug = format!("{:?}", mixed);
+    assert!(debug.starts_with("Mixed { nested: Some([1]), marker: PhantomData<"));
+    assert!(debug.ends_with("NotDebug>, value: 2, values: [3], both: (4, PhantomData<u8>) }"));
+}
@@ -0,0 +1,51 @@
+// A `#[debug(bound = "...")]` on a field replaces the bounds inferred from the
+// type of that field, while the other fields keep theirs. It can be used next
+// to a `#[debug = "..."]` format on the same field.
+
+use derive_debug::CustomDebug;
+use std::fmt::{Binary, Debug};
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Wrapper<T: Trait, U> {
+    #[debug(bound = "T::Value: Debug")]
+    field: Field<T>,
+    #[debug = "0b{:b}"]
+    #[debug(bound = "U: Binary")]
+    bits: U,
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T: Trait> {
+    values: Vec<T::Value>,
+}
+
+#[derive(CustomDebug)]
+#[debug(bound = "")]
+pub struct Unbounded<T> {
+    #[debug = "{}"]
+    len: usize,
+    marker: std::marker::PhantomData<T>,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    struct Id;
+
+    impl Trait for Id {
+        type Value = u8;
+    }
+
+    assert_debug::<Wrapper<Id, u8>>();
+    assert_debug::<Unbounded<Id>>();
+
+    let wrapper = Wrapper::<Id, u8> {
+        field: Field { values: vec![1] },
+        bits: 5,
+    };
+    assert_eq!(format!("{:?}", wrapper), "Wrapper { field: Field { values: [1] }, bits: 0b101 }");
+}
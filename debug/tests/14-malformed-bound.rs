@@ -0,0 +1,20 @@
+// A `debug(bound = "...")` that isn't a list of where-predicates is reported
+// where the bound is written, along with the other malformed attributes.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+#[debug(bound = "T Debug")]
+pub struct Wrapper<T> {
+    value: T,
+}
+
+#[derive(CustomDebug)]
+pub struct Field<T> {
+    #[debug(bound = "T: Debug;")]
+    value: T,
+    #[debug(typo)]
+    other: T,
+}
+
+fn main() {}
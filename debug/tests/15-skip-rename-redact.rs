@@ -0,0 +1,72 @@
+// A field with `#[debug(skip)]` is left out of the output, which ends with a
+// `..` the same way `finish_non_exhaustive` writes it, and needs no `Debug`.
+// A `#[debug(rename = "...")]` writes a named field under another name.
+//
+// A field with `#[debug(redact)]` is written as `<redacted>`, and one with
+// `#[debug(redact(keep = N))]` as `****` followed by the last N chars of its
+// `Display`, or of its `#[debug = "..."]` format when it has one. Neither
+// needs `Debug` for the type of the field, and the type params used by a
+// field that is masked through its `Display` are bound by `Display` instead.
+// A value no longer than N chars is masked completely rather than written
+// out in full.
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub struct NotDebug;
+
+#[derive(CustomDebug)]
+pub struct Login<T> {
+    #[debug(rename = "username")]
+    user: String,
+    #[debug(redact)]
+    password: T,
+    #[debug(redact(keep = 4))]
+    token: String,
+    #[debug = "{:x}"]
+    #[debug(redact(keep = 2))]
+    key: u32,
+    #[debug(skip)]
+    cache: NotDebug,
+}
+
+#[derive(CustomDebug)]
+pub struct Pair(u8, #[debug(skip)] NotDebug);
+
+#[derive(CustomDebug)]
+pub enum Event {
+    Short { #[debug(redact(keep = 8))] code: String },
+}
+
+#[derive(CustomDebug)]
+pub struct Secret<T> {
+    #[debug(redact(keep = 4))]
+    token: T,
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    assert_debug::<Login<NotDebug>>();
+    assert_debug::<Secret<String>>();
+
+    let login = Login {
+        user: "abby".to_owned(),
+        password: NotDebug,
+        token: "sk-0123456789abcd1234".to_owned(),
+        key: 0xbeef,
+        cache: NotDebug,
+    };
+    assert_eq!(
+        format!("{:?}", login),
+        r#"Login { username: "abby", password: <redacted>, token: ****1234, key: ****ef, .. }"#,
+    );
+
+    assert_eq!(format!("{:?}", Pair(1, NotDebug)), "Pair(1, ..)");
+
+    let event = Event::Short { code: "42".to_owned() };
+    assert_eq!(format!("{:?}", event), "Short { code: **** }");
+
+    let secret = Secret { token: 123456789 };
+    assert_eq!(format!("{:?}", secret), "Secret { token: ****6789 }");
+}
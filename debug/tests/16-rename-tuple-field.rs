@@ -0,0 +1,9 @@
+// A tuple field has no name in the output to replace, so a `rename` on one is
+// an error.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Pair(#[debug(rename = "first")] u8, u8);
+
+fn main() {}
@@ -6,7 +6,15 @@ fn tests() {
     t.pass("tests/03-custom-format.rs");
     t.pass("tests/04-type-parameter.rs");
     t.pass("tests/05-phantom-data.rs");
-    //t.pass("tests/06-bound-trouble.rs");
-    //t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/06-bound-trouble.rs");
+    t.pass("tests/07-associated-type.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enum-tuple.rs");
+    t.compile_fail("tests/10-union.rs");
+    t.pass("tests/11-pretty-print.rs");
+    t.pass("tests/12-bound-inference.rs");
+    t.pass("tests/13-field-bound.rs");
+    t.compile_fail("tests/14-malformed-bound.rs");
+    t.pass("tests/15-skip-rename-redact.rs");
+    t.compile_fail("tests/16-rename-tuple-field.rs");
 }